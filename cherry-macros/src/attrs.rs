@@ -1,20 +1,78 @@
 use proc_macro2::TokenStream;
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
 use syn::{Attribute, Ident, Path, Result, Token, Type};
 use quote::ToTokens;
 
 pub enum TableAttr {
     // table = <string>
     Table(String),
-    // id = <ident>
-    Id(Ident),
+    // id = <ident> | id(<ident>, ..)
+    Id(Id),
     // datasource = <ident>
     Datasource(Ident),
     // insertable [= [<attribute>]* <ident>]?
     Insertable(Option<Insertable>),
     // queryable [= [<attribute>]* <ident>]?
     Queryable(Option<Queryable>),
+    // has_many(<path>, foreign_key = <string> [, name = <ident>]?)
+    HasMany(Relation),
+    // has_one(<path>, foreign_key = <string> [, name = <ident>]?)
+    HasOne(Relation),
+    // belongs_to(<path>, foreign_key = <string> | foreign_key(<string>, ..) [, name = <ident>]?)
+    BelongsTo(Relation),
+    // soft_delete = <string>
+    SoftDelete(String),
+    // hooks
+    Hooks(()),
+    // rename_all = "snake_case" | "camelCase" | "SCREAMING_SNAKE_CASE"
+    RenameAll(RenameAll),
+    // always_quote
+    AlwaysQuote(()),
+    // schema = <string>
+    Schema(String),
+    // tenant_column = <string>
+    TenantColumn(String),
+    // columns
+    Columns(()),
+}
+
+/// naming convention of the columns of fields without `#[cherry(column = ..)]`.
+#[derive(Clone, Copy)]
+pub enum RenameAll {
+    Snake,
+    Camel,
+    ScreamingSnake,
+}
+
+impl RenameAll {
+    /// renames a snake_case field name.
+    pub fn apply(self, field: &str) -> String {
+        match self {
+            RenameAll::Snake => field.to_string(),
+            RenameAll::Camel => {
+                let mut out = String::with_capacity(field.len());
+                let mut upper = false;
+                for c in field.chars() {
+                    if c == '_' {
+                        upper = !out.is_empty();
+                    } else if upper {
+                        out.extend(c.to_uppercase());
+                        upper = false;
+                    } else {
+                        out.push(c);
+                    }
+                }
+                out
+            }
+            RenameAll::ScreamingSnake => field.to_uppercase(),
+        }
+    }
+}
+
+pub struct Id {
+    pub fields: Vec<Ident>,
 }
 
 pub struct Insertable {
@@ -27,6 +85,14 @@ pub struct Queryable {
     pub ident: Ident,
 }
 
+#[derive(Clone)]
+pub struct Relation {
+    pub target: Path,
+    /// One column, or one per id column of the target for `belongs_to`.
+    pub foreign_key: Vec<String>,
+    pub func: Option<Ident>,
+}
+
 pub enum TableFieldAttr {
     // column = <string>
     Column(String),
@@ -36,6 +102,8 @@ pub enum TableFieldAttr {
     Default(()),
     // unmapped, type must be Option
     Unmapped(()),
+    // computed, type must be Option, filled from a column selected by the query if there is one
+    Computed(()),
     // get_one [= <ident>]? [(<type>)]?
     GetOne(Getter),
     // get_optional [= <ident>]? [(<type>)]?
@@ -44,6 +112,16 @@ pub enum TableFieldAttr {
     GetMany(Getter),
     // set [= <ident>]?
     Set(Option<Ident>),
+    // created_at [= <string>]?
+    CreatedAt(Option<String>),
+    // updated_at [= <string>]?
+    UpdatedAt(Option<String>),
+    // version, type must be an integer
+    Version(()),
+    // json, type must implement serde's Serialize and DeserializeOwned
+    Json(()),
+    // with = <string>, path of a module with `to_db` and `from_db` functions
+    With(String),
 }
 
 #[derive(Clone)]
@@ -56,7 +134,14 @@ pub enum PatchAttr {
     // table = <string>
     TableName(String),
     Table(Path),
-    Id(String),
+    // id = <string> | id(<string>, ..)
+    Id(PatchId),
+    // hooks
+    Hooks(()),
+}
+
+pub struct PatchId {
+    pub columns: Vec<String>,
 }
 
 pub enum PatchFieldAttr {
@@ -65,6 +150,18 @@ pub enum PatchFieldAttr {
     CustomType(())
 }
 
+pub enum EnumAttr {
+    // repr = <type>
+    Repr(Box<Type>),
+    // type_name = <string>
+    TypeName(String),
+}
+
+pub enum EnumVariantAttr {
+    // rename = <string>
+    Rename(String),
+}
+
 impl Parse for Getter {
     fn parse(input: ParseStream) -> Result<Self> {
         let func = if input.peek(syn::token::Eq) {
@@ -84,6 +181,87 @@ impl Parse for Getter {
     }
 }
 
+impl Parse for Id {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let fields = if input.peek(syn::token::Eq) {
+            input.parse::<syn::token::Eq>()?;
+            vec![input.parse::<Ident>()?]
+        } else {
+            let content;
+            syn::parenthesized!(content in input);
+            Punctuated::<Ident, Token![,]>::parse_separated_nonempty(&content)?
+                .into_iter()
+                .collect()
+        };
+        Ok(Id { fields })
+    }
+}
+
+impl Parse for PatchId {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let columns = if input.peek(syn::token::Eq) {
+            input.parse::<syn::token::Eq>()?;
+            vec![input.parse::<syn::LitStr>()?.value()]
+        } else {
+            let content;
+            syn::parenthesized!(content in input);
+            Punctuated::<syn::LitStr, Token![,]>::parse_separated_nonempty(&content)?
+                .into_iter()
+                .map(|s| s.value())
+                .collect()
+        };
+        Ok(PatchId { columns })
+    }
+}
+
+impl Parse for Relation {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        syn::parenthesized!(content in input);
+        let target = content.parse::<Path>()?;
+        let mut foreign_key = None;
+        let mut func = None;
+        while !content.is_empty() {
+            content.parse::<Token![,]>()?;
+            if content.is_empty() {
+                break;
+            }
+            let key = content.parse::<Ident>()?;
+            if key == "foreign_key" && content.peek(syn::token::Paren) {
+                let columns;
+                syn::parenthesized!(columns in content);
+                let columns = Punctuated::<syn::LitStr, Token![,]>::parse_separated_nonempty(&columns)?;
+                foreign_key = Some(columns.into_iter().map(|s| s.value()).collect());
+                continue;
+            }
+            content.parse::<Token![=]>()?;
+            match &*key.to_string() {
+                "foreign_key" => foreign_key = Some(vec![content.parse::<syn::LitStr>()?.value()]),
+                "name" => func = Some(content.parse::<Ident>()?),
+                _ => return Err(syn::Error::new(key.span(), "unknown relation attribute")),
+            }
+        }
+        let foreign_key = foreign_key
+            .ok_or_else(|| syn::Error::new(target.span(), "missing foreign_key = <string>"))?;
+        Ok(Relation { target, foreign_key, func })
+    }
+}
+
+impl Parse for RenameAll {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let rule = input.parse::<syn::LitStr>()?;
+        match &*rule.value() {
+            "snake_case" => Ok(RenameAll::Snake),
+            "camelCase" => Ok(RenameAll::Camel),
+            "SCREAMING_SNAKE_CASE" => Ok(RenameAll::ScreamingSnake),
+            _ => Err(syn::Error::new(
+                rule.span(),
+                "expected \"snake_case\", \"camelCase\" or \"SCREAMING_SNAKE_CASE\"",
+            )),
+        }
+    }
+}
+
 impl Parse for Insertable {
     fn parse(input: ParseStream) -> Result<Self> {
         Ok(Self {
@@ -152,10 +330,10 @@ macro_rules! impl_parse {
         }
     };
     // parse "= {value}"
-    (= $x:tt) => ( |i: ParseStream| {
+    (= $($x:tt)+) => ( |i: ParseStream| {
         i.parse::<syn::Token![=]>()?;
         #[allow(clippy::redundant_closure_call)]
-        (impl_parse!($x))(i)
+        (impl_parse!($($x)+))(i)
     } );
     (String) => ( |i: ParseStream| i.parse().map(|s: syn::LitStr| s.value()) );
     (bool) => ( |i: ParseStream| i.parse().map(|s: syn::LitBool| s.value()) );
@@ -164,10 +342,20 @@ macro_rules! impl_parse {
 
 impl_parse!(TableAttr {
     "table" => Table(= String),
-    "id" => Id(= Ident),
+    "id" => Id(Id),
     "datasource" => Datasource(= Ident),
     "insertable" => Insertable((= Insertable)?),
-    "queryable" => Queryable((= Queryable)?)
+    "queryable" => Queryable((= Queryable)?),
+    "has_many" => HasMany(Relation),
+    "has_one" => HasOne(Relation),
+    "belongs_to" => BelongsTo(Relation),
+    "soft_delete" => SoftDelete(= String),
+    "hooks" => Hooks(),
+    "rename_all" => RenameAll(= RenameAll),
+    "always_quote" => AlwaysQuote(),
+    "schema" => Schema(= String),
+    "tenant_column" => TenantColumn(= String),
+    "columns" => Columns()
 });
 
 impl_parse!(TableFieldAttr {
@@ -178,13 +366,20 @@ impl_parse!(TableFieldAttr {
     "set" => Set((= Ident)?),
     "custom_type" => CustomType(),
     "default" => Default(),
-    "unmapped" => Unmapped()
+    "unmapped" => Unmapped(),
+    "computed" => Computed(),
+    "created_at" => CreatedAt((= String)?),
+    "updated_at" => UpdatedAt((= String)?),
+    "version" => Version(),
+    "json" => Json(),
+    "with" => With(= String)
 });
 
 impl_parse!(PatchAttr {
     "table" => Table(= Path),
     "table_name" => TableName(= String),
-    "id" => Id(= String)
+    "id" => Id(PatchId),
+    "hooks" => Hooks()
 });
 
 impl_parse!(PatchFieldAttr {
@@ -192,3 +387,11 @@ impl_parse!(PatchFieldAttr {
     "custom_type" => CustomType()
 });
 
+impl_parse!(EnumAttr {
+    "repr" => Repr(= Box<Type>),
+    "type_name" => TypeName(= String)
+});
+
+impl_parse!(EnumVariantAttr {
+    "rename" => Rename(= String)
+});
@@ -0,0 +1,106 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::backend::Backend;
+use crate::enums::{Enum, Repr};
+
+pub fn impl_enum<B: Backend>(e: &Enum) -> TokenStream {
+    let ident = &e.ident;
+    let variants: Vec<_> = e.variants.iter().map(|v| &v.ident).collect();
+    let names = e.variants.iter().map(|v| &v.name);
+    let db = quote!(cherry::types::Database);
+    let type_info = quote!(<#db as cherry::sqlx::Database>::TypeInfo);
+    let unknown = quote! {
+        Err(format!("unknown variant `{}` of {}", value, stringify!(#ident)).into())
+    };
+
+    let (impl_type, encode, decode) = match &e.repr {
+        Repr::Int(repr) => (
+            quote! {
+                fn type_info() -> #type_info {
+                    <#repr as cherry::sqlx::Type<#db>>::type_info()
+                }
+
+                fn compatible(ty: &#type_info) -> bool {
+                    <#repr as cherry::sqlx::Type<#db>>::compatible(ty)
+                }
+            },
+            quote! {
+                let value: #repr = match self {
+                    #( Self::#variants => Self::#variants as #repr, )*
+                };
+                <#repr as cherry::sqlx::Encode<'q, #db>>::encode(value, buf)
+            },
+            quote! {
+                let value = <#repr as cherry::sqlx::Decode<'r, #db>>::decode(value)?;
+                #(
+                    if value == Self::#variants as #repr {
+                        return Ok(Self::#variants);
+                    }
+                )*
+                #unknown
+            },
+        ),
+        repr => {
+            let impl_type = match repr {
+                Repr::Native(type_name) => quote! {
+                    fn type_info() -> #type_info {
+                        cherry::__native_enum_type_info!(#type_name)
+                    }
+
+                    fn compatible(ty: &#type_info) -> bool {
+                        *ty == <Self as cherry::sqlx::Type<#db>>::type_info()
+                    }
+                },
+                _ => quote! {
+                    fn type_info() -> #type_info {
+                        <str as cherry::sqlx::Type<#db>>::type_info()
+                    }
+
+                    fn compatible(ty: &#type_info) -> bool {
+                        <str as cherry::sqlx::Type<#db>>::compatible(ty)
+                    }
+                },
+            };
+            let encode_names = names.clone();
+            (
+                impl_type,
+                quote! {
+                    let value: &'static str = match self {
+                        #( Self::#variants => #encode_names, )*
+                    };
+                    <&str as cherry::sqlx::Encode<'q, #db>>::encode(value, buf)
+                },
+                quote! {
+                    match <&str as cherry::sqlx::Decode<'r, #db>>::decode(value)? {
+                        #( #names => Ok(Self::#variants), )*
+                        value => #unknown,
+                    }
+                },
+            )
+        }
+    };
+
+    quote! {
+        impl cherry::sqlx::Type<#db> for #ident {
+            #impl_type
+        }
+
+        impl<'q> cherry::sqlx::Encode<'q, #db> for #ident {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <#db as cherry::sqlx::HasArguments<'q>>::ArgumentBuffer,
+            ) -> cherry::sqlx::IsNull {
+                #encode
+            }
+        }
+
+        impl<'r> cherry::sqlx::Decode<'r, #db> for #ident {
+            fn decode(
+                value: <#db as cherry::sqlx::HasValueRef<'r>>::ValueRef,
+            ) -> Result<Self, cherry::sqlx::BoxDynError> {
+                #decode
+            }
+        }
+    }
+}
@@ -6,14 +6,16 @@ use syn::{Ident, Type, Visibility};
 
 pub use table::*;
 pub use schema::*;
+pub use enums::*;
 
-use crate::attrs::{Insertable};
+use crate::attrs::{Insertable, Relation};
 use crate::backend::Backend;
 use crate::patch::Patch;
 use crate::table::Table;
 
 mod table;
 mod schema;
+mod enums;
 
 pub(crate) fn getters<B: Backend>(table: &Table<B>) -> TokenStream {
     let column_list = table.select_column_list();
@@ -21,27 +23,29 @@ pub(crate) fn getters<B: Backend>(table: &Table<B>) -> TokenStream {
     let mut getters = TokenStream::new();
 
     for field in table.fields.iter() {
+        let condition = format!("{} = {}", field.column(), B::Bindings::default().next().unwrap());
         let sql = format!(
-            "SELECT {} FROM {} WHERE {} = {}",
+            "SELECT {} FROM {} WHERE {}",
             column_list,
-            table.table,
-            field.column(),
-            B::Bindings::default().next().unwrap()
+            table.table_name(),
+            table.not_deleted(&condition)
         );
 
+        let query = table.query_as(&sql, &[quote!(by)]);
+
         if let Some(getter) = &field.get_one {
             let (func, arg) = getter.or_fallback(field);
-            getters.extend(get_one(vis, &func, &arg, &sql));
+            getters.extend(get_one(vis, &func, &arg, &query));
         }
 
         if let Some(getter) = &field.get_optional {
             let (func, arg) = getter.or_fallback(field);
-            getters.extend(get_optional(vis, &func, &arg, &sql));
+            getters.extend(get_optional(vis, &func, &arg, &query));
         }
 
         if let Some(getter) = &field.get_many {
             let (func, arg) = getter.or_fallback(field);
-            getters.extend(get_many(vis, &func, &arg, &sql));
+            getters.extend(get_many(vis, &func, &arg, &query));
         }
     }
 
@@ -53,36 +57,36 @@ pub(crate) fn getters<B: Backend>(table: &Table<B>) -> TokenStream {
     }
 }
 
-pub fn get_one(vis: &Visibility, ident: &Ident, by_ty: &Type, sql: &str) -> TokenStream {
+pub fn get_one(vis: &Visibility, ident: &Ident, by_ty: &Type, query: &TokenStream) -> TokenStream {
     quote! {
         #vis async fn #ident(
             by: #by_ty,
         ) -> sqlx::Result<Self> {
-            sqlx::query_as!(Self, #sql, by)
+            #query
                 .fetch_one(Self::pool()?)
                 .await
         }
     }
 }
 
-pub fn get_optional(vis: &Visibility, ident: &Ident, by_ty: &Type, sql: &str) -> TokenStream {
+pub fn get_optional(vis: &Visibility, ident: &Ident, by_ty: &Type, query: &TokenStream) -> TokenStream {
     quote! {
         #vis async fn #ident(
             by: #by_ty,
         ) -> sqlx::Result<Option<Self>> {
-            sqlx::query_as!(Self, #sql, by)
+            #query
                 .fetch_optional(Self::pool()?)
                 .await
         }
     }
 }
 
-pub fn get_many(vis: &Visibility, ident: &Ident, by_ty: &Type, sql: &str) -> TokenStream {
+pub fn get_many(vis: &Visibility, ident: &Ident, by_ty: &Type, query: &TokenStream) -> TokenStream {
     quote! {
         #vis async fn #ident(
             by: #by_ty,
         ) -> sqlx::Result<Vec<Self>> {
-            sqlx::query_as!(Self, #sql, by)
+            #query
                 .fetch_all(Self::pool()?)
                 .await
         }
@@ -100,19 +104,21 @@ pub fn setters<B: Backend>(table: &Table<B>) -> TokenStream {
         if let Some(fn_name) = &field.set {
             let mut bindings = B::Bindings::default();
             let sql = format!(
-                "UPDATE {} SET {} = {} WHERE {} = {}",
-                table.table,
+                "UPDATE {} SET {} = {} WHERE {}",
+                table.table_name(),
                 field.column(),
                 bindings.next().unwrap(),
-                table.id.column(),
-                bindings.next().unwrap(),
+                table.id_condition(&mut bindings),
             );
+            let id_arguments = table.id_arguments(&quote!(id));
+            let value_argument = field.argument(quote!(value));
             setters.extend(quote! {
                 #vis async fn #fn_name(
                     &mut self,
                     value: #field_ty
                 ) -> sqlx::Result<()> {
-                    sqlx::query!(#sql, value, <Self as cherry::Table>::id(self))
+                    let id = <Self as cherry::Table>::id(self);
+                    sqlx::query!(#sql, #value_argument, #( #id_arguments, )*)
                         .execute(Self::pool()?)
                         .await?;
                     self.#field_ident = value;
@@ -130,6 +136,70 @@ pub fn setters<B: Backend>(table: &Table<B>) -> TokenStream {
     }
 }
 
+pub(crate) fn relations<B: Backend>(table: &Table<B>) -> TokenStream {
+    let vis = &table.vis;
+    let mut relations = TokenStream::new();
+
+    let to_many = table.has_many.iter().map(|r| (r, true));
+    let to_one = table.has_one.iter().map(|r| (r, false));
+    for (relation, plural) in to_many.chain(to_one) {
+        let target = &relation.target;
+        let foreign_key = &relation.foreign_key[0];
+        let fn_name = relation_fn(relation, plural);
+        let id_ident = &table.id[0].field;
+        relations.extend(quote! {
+            #vis fn #fn_name<'a>(&self) -> cherry::Select<'a, #target> {
+                <#target as cherry::Schema>::select()
+                    .and_where_eq(#foreign_key, ::std::clone::Clone::clone(&self.#id_ident))
+            }
+        });
+    }
+
+    let mut checks = TokenStream::new();
+    for relation in &table.belongs_to {
+        let target = &relation.target;
+        let fn_name = relation_fn(relation, false);
+        let count = relation.foreign_key.len();
+        let index = 0..count;
+        let foreign_keys = relation.foreign_key.iter().map(|column| {
+            &table.fields.iter().find(|field| field.column_name == *column).unwrap().field
+        });
+        let message = format!(
+            "belongs_to({}) needs one foreign_key per id column of the target",
+            quote!(#target).to_string().replace(' ', ""),
+        );
+        checks.extend(quote! {
+            const _: () = assert!(<#target as cherry::Table>::ID_COUNT == #count, #message);
+        });
+        relations.extend(quote! {
+            #vis fn #fn_name<'a>(&self) -> cherry::Select<'a, #target> {
+                let id_columns = <#target as cherry::Table>::id_columns();
+                <#target as cherry::Schema>::select()
+                    #( .and_where_eq(id_columns[#index], ::std::clone::Clone::clone(&self.#foreign_keys)) )*
+            }
+        });
+    }
+
+    let table_ident = &table.ident;
+    quote! {
+        impl #table_ident {
+            #relations
+        }
+
+        #checks
+    }
+}
+
+/// name of a relation accessor, derived from the target type unless given explicitly.
+fn relation_fn(relation: &Relation, plural: bool) -> Ident {
+    relation.func.clone().unwrap_or_else(|| {
+        let target = &relation.target.segments.last().unwrap().ident;
+        let name = crate::utils::snake_case(&target.to_string());
+        let name = if plural { crate::utils::plural(&name) } else { name };
+        Ident::new(&name, target.span())
+    })
+}
+
 pub(crate) fn impl_patch<B: Backend>(patch: &Patch) -> TokenStream {
     let patch_ident = &patch.ident;
     let table_path = &patch.table;
@@ -138,34 +208,37 @@ pub(crate) fn impl_patch<B: Backend>(patch: &Patch) -> TokenStream {
         .iter()
         .map(|field| &field.ident)
         .collect::<Vec<&Ident>>();
-    let query_args = &patch
-        .fields
-        .iter()
-        .map(|field| {
-            let field_ident = &field.ident;
-            let field_ty = &field.ty;
-            match field.custom_type {
-                false => quote!(#field_ident),
-                true => quote!(#field_ident as #field_ty)
-            }
-        })
-        .collect::<Vec<TokenStream>>();
-
-    let mut bindings = B::Bindings::default();
-    let mut assignments = Vec::with_capacity(patch.fields.len());
-    for field in &patch.fields {
-        let fragment = format!("{} = {}", field.column, bindings.next().unwrap());
-        assignments.push(fragment);
-    }
-    let assignments = assignments.join(", ");
+    let field_columns = patch.fields.iter().map(|field| &field.column);
+
+    let id_columns = &patch.id;
+    let id_arguments = match patch.id.len() {
+        1 => vec![quote!(id)],
+        len => (0..len)
+            .map(|i| {
+                let i = syn::Index::from(i);
+                quote!(id.#i)
+            })
+            .collect(),
+    };
 
-    let sql = format!(
-        "UPDATE {} SET {} WHERE {} = {}",
-        &patch.table_name,
-        assignments,
-        patch.id,
-        bindings.next().unwrap()
-    );
+    let hooks = |tx: TokenStream| match patch.hooks {
+        true => (
+            quote! {
+                cherry::Hooks::before_update(self, #tx).await.map_err(cherry::error::into_sqlx)?;
+            },
+            quote! {
+                cherry::Hooks::after_update(self, #tx).await.map_err(cherry::error::into_sqlx)?;
+            },
+        ),
+        false => (quote!(), quote!()),
+    };
+    let (before_update, after_update) = hooks(quote!(None));
+    let (before_update_tx, after_update_tx) = hooks(quote!(Some(&mut *tx)));
+    let update = quote! {
+        <Self::Table as cherry::Schema>::update()
+            #( .set(#field_columns, &self.#field_idents) )*
+            #( .and_where_eq(#id_columns, #id_arguments) )*
+    };
 
     let box_future = crate::utils::box_future();
     quote! {
@@ -193,15 +266,33 @@ pub(crate) fn impl_patch<B: Backend>(patch: &Patch) -> TokenStream {
                 }
             }
 
-            fn patch_row_with<'a, 'c: 'a>(
+            fn patch_row_versioned_with<'a, 'c: 'a>(
                 &'a self,
                 db: impl sqlx::Executor<'c, Database = cherry::Db> + 'a,
                 id: <Self::Table as cherry::Table>::Id,
+                version: Option<i64>,
             ) -> #box_future<'a, sqlx::Result<()>> {
                 Box::pin(async move {
-                    sqlx::query!(#sql, #( self.#query_args, )* id)
-                        .execute(db)
-                        .await?;
+                    #before_update
+                    let mut update = #update;
+                    if let Some(version) = version {
+                        update = update.with_version(version);
+                    }
+                    update.execute_on(db).await?;
+                    #after_update
+                    Ok(())
+                })
+            }
+
+            fn patch_row_tx<'a, 'c: 'a>(
+                &'a self,
+                tx: &'a mut cherry::types::Transaction<'c>,
+                id: <Self::Table as cherry::Table>::Id,
+            ) -> #box_future<'a, sqlx::Result<()>> {
+                Box::pin(async move {
+                    #before_update_tx
+                    #update.execute_on(&mut *tx).await?;
+                    #after_update_tx
                     Ok(())
                 })
             }
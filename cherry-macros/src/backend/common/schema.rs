@@ -1,10 +1,12 @@
 use itertools::Itertools;
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, TokenStream};
 use quote::quote;
+use syn::ext::IdentExt;
 
 use crate::{
     backend::Backend,
     schema::{Schema},
+    table::{TableField, TimestampKind},
 };
 
 pub fn impl_schema<B: Backend>(table: &Schema<B>) -> TokenStream {
@@ -14,7 +16,35 @@ pub fn impl_schema<B: Backend>(table: &Schema<B>) -> TokenStream {
     let name = name::<B>(table);
     let columns = columns::<B>(table);
     let arguments = arguments::<B>(table);
+    let column_arguments = column_arguments::<B>(table);
     let from_row = from_row::<B>(table);
+    let soft_delete = soft_delete::<B>(table);
+    let tenant = tenant::<B>(table);
+    let holders = holders::<B>(table);
+    let touch = touch::<B>(table);
+    let version = version::<B>(table);
+    let column_refs = column_refs::<B>(table);
+    let always_quote = match table.always_quote {
+        true => quote! {
+            fn always_quote() -> bool { true }
+        },
+        false => quote!(),
+    };
+    let schema = table.schema.as_ref().map(|schema| quote! {
+        fn schema() -> Option<&'static str> { Some(#schema) }
+    });
+    let hooks = match table.hooks {
+        true => quote! {
+            fn hooks(&self) -> Option<&dyn cherry::Hooks> {
+                Some(self)
+            }
+
+            fn hooks_mut(&mut self) -> Option<&mut dyn cherry::Hooks> {
+                Some(self)
+            }
+        },
+        false => quote!(),
+    };
 
     quote! {
         impl cherry::Schema for #table_ident {
@@ -23,8 +53,53 @@ pub fn impl_schema<B: Backend>(table: &Schema<B>) -> TokenStream {
             #name
             #columns
             #arguments
+            #column_arguments
             #from_row
+            #soft_delete
+            #tenant
+            #holders
+            #touch
+            #version
+            #hooks
+            #always_quote
+            #schema
         }
+
+        #column_refs
+    }
+}
+
+/// a `cherry::Column` constant per mapped field, named after the field in upper case,
+/// with `#[cherry(columns)]`.
+fn column_refs<B: Backend>(table: &Schema<B>) -> TokenStream {
+    if !table.columns {
+        return quote!();
+    }
+    let table_ident = &table.ident;
+    let vis = &table.vis;
+    let consts = table.mapped_fields().map(|field| {
+        let ident = Ident::new(&field.field.unraw().to_string().to_uppercase(), field.field.span());
+        let column = &field.column_name;
+        quote! {
+            #vis const #ident: cherry::Column<Self> = cherry::Column::new(#column);
+        }
+    });
+
+    quote! {
+        impl #table_ident {
+            #( #consts )*
+        }
+    }
+}
+
+fn soft_delete<B: Backend>(table: &Schema<B>) -> TokenStream {
+    match &table.soft_delete {
+        Some(column) => quote! {
+            fn soft_delete_column() -> Option<&'static str> {
+                Some(#column)
+            }
+        },
+        None => quote!(),
     }
 }
 
@@ -40,44 +115,187 @@ fn name<B: Backend>(table: &Schema<B>) -> TokenStream {
 }
 
 fn columns<B: Backend>(table: &Schema<B>) -> TokenStream {
-    let fields : proc_macro2::TokenStream = table.mapped_fields()
-        .map(|s|
-            format!(" \"{}\"", s.column())
-        ).join(", ").parse().unwrap();
+    let columns = table.mapped_fields().map(|field| &field.column_name);
 
     quote! {
         fn columns() -> Vec<&'static str> {
-                vec![ #fields]
-            }
+            vec![ #( #columns, )* ]
+        }
     }
 }
 
 fn arguments<B: Backend>(table: &Schema<B>) -> TokenStream {
-    let arguments : proc_macro2::TokenStream = table.mapped_fields()
-        .map(|s|
-            format!(" arguments.add(&self.{}); ", s.field)
-        ).collect::<String>().parse().unwrap();
+    let arguments = add_arguments(table, None);
 
     quote! {
         fn arguments<'a>(&'a self, arguments: &mut cherry::types::Arguments<'a>) {
             use cherry::sqlx::Arguments as OtherArguments;
             #arguments
         }
+
+        fn copy_arguments(&self, arguments: &mut cherry::CopyRow) -> Result<(), cherry::error::Error> {
+            #arguments
+            Ok(())
+        }
+    }
+}
+
+/// the column filled by a tenant scope, and the arguments of the other columns.
+fn tenant<B: Backend>(table: &Schema<B>) -> TokenStream {
+    let column = match &table.tenant_column {
+        Some(column) => column,
+        None => return quote!(),
+    };
+    let arguments = add_arguments(table, Some(column));
+
+    quote! {
+        fn tenant_column() -> Option<&'static str> {
+            Some(#column)
+        }
+
+        fn tenant_arguments<'a>(&'a self, arguments: &mut cherry::types::Arguments<'a>) {
+            use cherry::sqlx::Arguments as OtherArguments;
+            #arguments
+        }
+    }
+}
+
+/// adds the argument of each column bound by a placeholder, except the column `skip`.
+fn add_arguments<B: Backend>(table: &Schema<B>, skip: Option<&str>) -> TokenStream {
+    table.mapped_fields()
+        .filter(|field| field.timestamp_expr().is_none())
+        .filter(|field| Some(field.column_name.as_str()) != skip)
+        .map(|field| match field.timestamp {
+            Some(_) => {
+                let now = field.now();
+                quote!( arguments.add(#now); )
+            }
+            None => {
+                let argument = argument(field);
+                quote!( arguments.add(#argument); )
+            }
+        })
+        .collect()
+}
+
+/// reference to the value of a field, serialized for json fields and converted `with` a module.
+fn argument<B: Backend>(field: &TableField<B>) -> TokenStream {
+    let ident = &field.field;
+    match (&field.with, field.json) {
+        (Some(with), _) => quote!(#with::to_db(&self.#ident)),
+        (None, true) => quote!(cherry::sqlx::Json(&self.#ident)),
+        (None, false) => quote!(&self.#ident),
+    }
+}
+
+fn version<B: Backend>(table: &Schema<B>) -> TokenStream {
+    match table.mapped_fields().find(|field| field.version) {
+        Some(field) => {
+            let column = &field.column_name;
+            quote! {
+                fn version_column() -> Option<&'static str> {
+                    Some(#column)
+                }
+            }
+        }
+        None => quote!(),
+    }
+}
+
+fn column_arguments<B: Backend>(table: &Schema<B>) -> TokenStream {
+    let columns = table.mapped_fields().map(|field| &field.column_name);
+    let fields = table.mapped_fields().map(argument);
+
+    quote! {
+        fn column_arguments<'a>(
+            &'a self,
+            columns: &[&str],
+            arguments: &mut cherry::types::Arguments<'a>,
+        ) {
+            use cherry::sqlx::Arguments as OtherArguments;
+            #(
+                if columns.contains(&#columns) {
+                    arguments.add(#fields);
+                }
+            )*
+        }
+    }
+}
+
+/// placeholders of the columns, replaced by the expression of database filled timestamps.
+fn holders<B: Backend>(table: &Schema<B>) -> TokenStream {
+    if table.mapped_fields().all(|field| field.timestamp_expr().is_none()) {
+        return quote!();
+    }
+    let holders = table.mapped_fields().map(|field| field.timestamp_expr().unwrap_or("?"));
+
+    quote! {
+        fn holders() -> Vec<&'static str> {
+            vec![ #( #holders, )* ]
+        }
+    }
+}
+
+/// sets the `updated_at` column, if there is one.
+fn touch<B: Backend>(table: &Schema<B>) -> TokenStream {
+    let field = match table.mapped_fields().find(|field| field.is_timestamp(TimestampKind::Updated)) {
+        Some(field) => field,
+        None => return quote!(),
+    };
+    let column = &field.column_name;
+    let (arguments, touch) = match field.timestamp_expr() {
+        Some(expr) => (quote!(_arguments), quote!( Some((#column, #expr)) )),
+        None => {
+            let now = field.now();
+            let touch = quote! {
+                use cherry::sqlx::Arguments as OtherArguments;
+                arguments.add(#now);
+                Some((#column, "?"))
+            };
+            (quote!(arguments), touch)
+        }
+    };
+
+    quote! {
+        fn updated_at_column() -> Option<&'static str> {
+            Some(#column)
+        }
+
+        fn touch<'a>(
+            #arguments: &mut cherry::types::Arguments<'a>,
+        ) -> Option<(&'static str, &'static str)> {
+            #touch
+        }
     }
 }
 
 fn from_row<B: Backend>(table: &Schema<B>) -> TokenStream {
     let from_row : proc_macro2::TokenStream = table.mapped_fields()
-        .map(|field|
-            format!(" {0}: row.try_get(\"{1}\")?", field.field, field.column())
-        ).join(", ").parse().unwrap();
+        .map(|field| match (&field.with, field.json) {
+            (Some(with), _) => format!(
+                " {0}: {2}::from_db(row.try_get(\"{1}\")?)?",
+                field.field,
+                field.column_name,
+                quote!(#with)
+            ),
+            (None, true) => format!(
+                " {0}: row.try_get::<cherry::sqlx::Json<_>, _>(\"{1}\")?.0",
+                field.field,
+                field.column_name
+            ),
+            (None, false) => format!(" {0}: row.try_get(\"{1}\")?", field.field, field.column_name),
+        }).join(", ").parse().unwrap();
 
     let defaults : proc_macro2::TokenStream = table.unmapped_fields()
-        .map(|field|
-            format!(" {0}: Default::default()", field.field)
-        ).join(", ").parse().unwrap();
+        .map(|field| match field.computed {
+            true => format!(
+                " {0}: cherry::error::computed(row.try_get(\"{1}\"))?",
+                field.field,
+                field.column_name
+            ),
+            false => format!(" {0}: Default::default()", field.field),
+        }).join(", ").parse().unwrap();
 
-    //@TODO add unmapped fields
     quote! {
         fn from_row(row: &cherry::types::Row) -> Result<Self, cherry::error::Error> {
             use cherry::sqlx::Row as OtherRow;
@@ -1,26 +1,55 @@
 use proc_macro2::TokenStream;
-use quote::{quote, TokenStreamExt};
+use quote::quote;
 
 use crate::backend::Backend;
-use crate::table::Table;
+use crate::table::{Table, TimestampKind};
 
 pub fn impl_table<B: Backend>(table: &Table<B>) -> TokenStream {
     let table_ident = &table.ident;
-    let id_ident = &table.id.field;
-    let id_ty = &table.id.ty;
+    let id_ty = table.id_ty();
+    let id_fields = table.id.iter().map(|id| &id.field);
+    let id = match table.id.as_slice() {
+        [id] => {
+            let id = &id.field;
+            quote!(self.#id)
+        }
+        _ => quote!(( #( self.#id_fields, )* )),
+    };
     let column_list = table.select_column_list();
+    let id_columns = table.id.iter().map(|id| &id.column_name);
+    let id_count = table.id.len();
+    let id_arguments = table.id_arguments(&quote!(id));
 
     let get = get::<B>(table, &column_list);
     let stream_all = stream_all(table, &column_list);
     let stream_all_paginated = stream_all_paginated::<B>(table, &column_list);
     let update = update::<B>(table);
     let delete = delete::<B>(table);
+    let version = table.version_field().map(|version| {
+        let ident = &version.field;
+        quote! {
+            fn version(&self) -> Option<i64> { Some(self.#ident as i64) }
+
+            fn bump_version(&mut self) { self.#ident += 1; }
+        }
+    });
 
     quote! {
         impl cherry::Table for #table_ident {
             type Id = #id_ty;
 
-            fn id(&self) -> Self::Id { self.#id_ident }
+            fn id(&self) -> Self::Id { #id }
+
+            const ID_COUNT: usize = #id_count;
+
+            fn id_columns() -> Vec<&'static str> { vec![ #( #id_columns, )* ] }
+
+            fn id_arguments(id: Self::Id, arguments: &mut cherry::types::Arguments<'_>) {
+                use cherry::sqlx::Arguments as OtherArguments;
+                #( arguments.add(#id_arguments); )*
+            }
+
+            #version
 
             #get
             #stream_all
@@ -34,12 +63,12 @@ pub fn impl_table<B: Backend>(table: &Table<B>) -> TokenStream {
 fn get<B: Backend>(table: &Table<B>, column_list: &str) -> TokenStream {
     let box_future = crate::utils::box_future();
     let get_sql = format!(
-        "SELECT {} FROM {} WHERE {} = {}",
+        "SELECT {} FROM {} WHERE {}",
         column_list,
-        table.table,
-        table.id.column(),
-        B::Bindings::default().next().unwrap()
+        table.table_name(),
+        table.not_deleted(&table.id_condition(&mut B::Bindings::default()))
     );
+    let query = table.query_as(&get_sql, &table.id_arguments(&quote!(id)));
 
     quote! {
 
@@ -47,7 +76,7 @@ fn get<B: Backend>(table: &Table<B>, column_list: &str) -> TokenStream {
             id: Self::Id,
         ) -> #box_future<'a, sqlx::Result<Self>> {
             Box::pin(async move {
-                sqlx::query_as!(Self, #get_sql, id)
+                #query
                     .fetch_one(Self::pool()?)
                     .await
             })
@@ -57,37 +86,76 @@ fn get<B: Backend>(table: &Table<B>, column_list: &str) -> TokenStream {
 
 fn update<B: Backend>(table: &Table<B>) -> TokenStream {
     let box_future = crate::utils::box_future();
+    let update_fields: Vec<_> = table
+        .fields_except_id()
+        .filter(|field| !field.is_timestamp(TimestampKind::Created) && !field.version)
+        .collect();
     let mut bindings = B::Bindings::default();
     let mut assignments = vec![];
-    for field in table.fields_except_id() {
-        let fragment = format!("{} = {}", field.column(), bindings.next().unwrap());
-        assignments.push(fragment);
+    for field in &update_fields {
+        let holder = match field.timestamp_expr() {
+            Some(expr) => expr.into(),
+            None => bindings.next().unwrap(),
+        };
+        assignments.push(format!("{} = {}", field.column(), holder));
+    }
+    let mut condition = table.id_condition(&mut bindings);
+    let version = table.version_field();
+    if let Some(version) = version {
+        assignments.push(format!("{0} = {0} + 1", version.column()));
+        condition = format!("{} AND {} = {}", condition, version.column(), bindings.next().unwrap());
     }
     let assignments = assignments.join(", ");
 
     let update_sql = format!(
-        "UPDATE {} SET {} WHERE {} = {}",
-        table.table,
+        "UPDATE {} SET {} WHERE {}",
+        table.table_name(),
         assignments,
-        table.id.column(),
-        bindings.next().unwrap()
+        condition
     );
-    let id_argument = &table.id.field;
-    let other_arguments = table.fields_except_id().map(|field| {
-        let ident = &field.field;
-        let mut out = quote!(self.#ident);
-
-        if field.custom_type {
-            let ty = &field.ty;
-            out.append_all(quote!(as #ty))
+    let id_arguments = table.id.iter().chain(version).map(|id| &id.field);
+    let check_version = version.map(|version| {
+        let ident = &version.field;
+        quote! {
+            if result.rows_affected() == 0 {
+                return Err(cherry::error::StaleObject::new(<Self as cherry::Schema>::table()).into());
+            }
+            self.#ident += 1;
         }
-
-        out
     });
+    let other_arguments = update_fields
+        .iter()
+        .filter(|field| field.timestamp_expr().is_none())
+        .map(|field| {
+            if field.timestamp.is_some() {
+                return field.now();
+            }
+
+            let ident = &field.field;
+            field.argument(quote!(self.#ident))
+        });
+
+    let hooks = |tx: TokenStream| match table.hooks {
+        true => (
+            quote! {
+                cherry::Hooks::normalize(&mut *self);
+                cherry::Hooks::before_update(&*self, #tx).await.map_err(cherry::error::into_sqlx)?;
+            },
+            quote! {
+                cherry::Hooks::after_update(&*self, #tx).await.map_err(cherry::error::into_sqlx)?;
+            },
+        ),
+        false => (quote!(), quote!()),
+    };
+    let (before_update, after_update) = hooks(quote!(None));
+    let (before_update_tx, after_update_tx) = hooks(quote!(Some(&mut *tx)));
+    let query = quote! {
+        sqlx::query!(#update_sql, #( #other_arguments, )* #( self.#id_arguments, )*)
+    };
 
     quote! {
         fn update<'a>(
-            &'a self,
+            &'a mut self,
         ) -> #box_future<'a, sqlx::Result<()>> {
             Box::pin(async move {
                 let mut pool = Self::pool()?;
@@ -98,13 +166,31 @@ fn update<B: Backend>(table: &Table<B>) -> TokenStream {
         }
 
         fn update_with<'a, 'c: 'a>(
-        &'a self,
+        &'a mut self,
         db: impl sqlx::Executor<'c, Database = cherry::Db> + 'a,
         ) -> #box_future<'a, sqlx::Result<()>> {
             Box::pin(async move {
-                sqlx::query!(#update_sql, #( #other_arguments, )* self.#id_argument)
+                #before_update
+                let result = #query
                     .execute(db)
                     .await?;
+                #check_version
+                #after_update
+                Ok(())
+            })
+        }
+
+        fn update_tx<'a, 'c: 'a>(
+            &'a mut self,
+            tx: &'a mut cherry::types::Transaction<'c>,
+        ) -> #box_future<'a, sqlx::Result<()>> {
+            Box::pin(async move {
+                #before_update_tx
+                let result = #query
+                    .execute(&mut *tx)
+                    .await?;
+                #check_version
+                #after_update_tx
                 Ok(())
             })
         }
@@ -113,13 +199,17 @@ fn update<B: Backend>(table: &Table<B>) -> TokenStream {
 
 fn stream_all<B: Backend>(table: &Table<B>, column_list: &str) -> TokenStream {
     let box_stream = crate::utils::box_stream();
-    let all_sql = format!("SELECT {} FROM {}", column_list, table.table);
+    let all_sql = match table.not_deleted("").as_str() {
+        "" => format!("SELECT {} FROM {}", column_list, table.table_name()),
+        condition => format!("SELECT {} FROM {} WHERE {}", column_list, table.table_name(), condition),
+    };
+    let query = table.query_as(&all_sql, &[]);
 
     quote! {
         fn stream_all<'a>(
         ) -> #box_stream<'a, sqlx::Result<Self>> {
             let pool = Self::pool().unwrap(); //@TODO figure out how to surface this error
-            sqlx::query_as!(Self, #all_sql)
+            #query
                 .fetch(pool)
         }
     }
@@ -128,13 +218,19 @@ fn stream_all<B: Backend>(table: &Table<B>, column_list: &str) -> TokenStream {
 fn stream_all_paginated<B: Backend>(table: &Table<B>, column_list: &str) -> TokenStream {
     let box_stream = crate::utils::box_stream();
     let mut bindings = B::Bindings::default();
+    let condition = match table.not_deleted("").as_str() {
+        "" => String::new(),
+        condition => format!(" WHERE {}", condition),
+    };
     let all_sql = format!(
-        "SELECT {} FROM {} LIMIT {} OFFSET {}",
+        "SELECT {} FROM {}{} LIMIT {} OFFSET {}",
         column_list,
-        table.table,
+        table.table_name(),
+        condition,
         bindings.next().unwrap(),
         bindings.next().unwrap()
     );
+    let query = table.query_as(&all_sql, &[quote!(limit), quote!(offset)]);
 
     quote! {
         fn stream_all_paginated<'a>(
@@ -142,22 +238,51 @@ fn stream_all_paginated<B: Backend>(table: &Table<B>, column_list: &str) -> Toke
             limit: i64,
         ) -> #box_stream<'a, sqlx::Result<Self>> {
             let pool = Self::pool().unwrap(); //@TODO figure out how to surface this error
-            sqlx::query_as!(Self, #all_sql, limit, offset)
+            #query
                 .fetch(pool)
         }
     }
 }
 
 fn delete<B: Backend>(table: &Table<B>) -> TokenStream {
+    let id_condition = table.id_condition(&mut B::Bindings::default());
+    let delete_sql = format!("DELETE FROM {} WHERE {}", table.table_name(), id_condition);
+
+    match &table.soft_delete {
+        Some(field) => {
+            let soft_delete_sql = format!(
+                "UPDATE {} SET {} = CURRENT_TIMESTAMP WHERE {} AND {} IS NULL",
+                table.table_name(),
+                field.column(),
+                id_condition,
+                field.column()
+            );
+            let restore_sql = format!(
+                "UPDATE {} SET {} = NULL WHERE {} AND {} IS NOT NULL",
+                table.table_name(),
+                field.column(),
+                id_condition,
+                field.column()
+            );
+            let delete_row_with = execute_by_id(table, &quote!(delete_row_with), &soft_delete_sql);
+            let force_delete_row_with = execute_by_id(table, &quote!(force_delete_row_with), &delete_sql);
+            let restore_row_with = execute_by_id(table, &quote!(restore_row_with), &restore_sql);
+            quote! {
+                #delete_row_with
+                #force_delete_row_with
+                #restore_row_with
+            }
+        }
+        None => execute_by_id(table, &quote!(delete_row_with), &delete_sql),
+    }
+}
+
+/// a function executing `sql` with the given id, failing if no row was affected.
+fn execute_by_id<B: Backend>(table: &Table<B>, func: &TokenStream, sql: &str) -> TokenStream {
     let box_future = crate::utils::box_future();
-    let id_ty = &table.id.ty;
-    let delete_sql = format!(
-        "DELETE FROM {} WHERE {} = {}",
-        table.table,
-        table.id.column(),
-        B::Bindings::default().next().unwrap()
-    );
-    #[cfg(feature = "mysql")]
+    let id_ty = table.id_ty();
+    let id_arguments = table.id_arguments(&quote!(id));
+    #[cfg(all(feature = "mysql", not(feature = "sqlite")))]
     let result_import = quote!(sqlx::mysql::MySqlQueryResult);
     #[cfg(feature = "postgres")]
     let result_import = quote!(sqlx::postgres::PgQueryResult);
@@ -165,14 +290,14 @@ fn delete<B: Backend>(table: &Table<B>) -> TokenStream {
     let result_import = quote!(sqlx::sqlite::SqliteQueryResult);
 
     quote! {
-        fn delete_row_with<'a, 'c: 'a>(
+        fn #func<'a, 'c: 'a>(
             db: impl sqlx::Executor<'c, Database = cherry::Db> + 'a,
             id: #id_ty
         ) -> #box_future<'a, sqlx::Result<()>> {
             use #result_import;
 
             Box::pin(async move {
-                let result = sqlx::query!(#delete_sql, id)
+                let result = sqlx::query!(#sql, #( #id_arguments, )*)
                     .execute(db)
                     .await?;
                 if result.rows_affected() == 0 {
@@ -184,4 +309,3 @@ fn delete<B: Backend>(table: &Table<B>) -> TokenStream {
         }
     }
 }
-
@@ -2,6 +2,7 @@ use std::borrow::Cow;
 
 use proc_macro2::TokenStream;
 
+use crate::enums::Enum;
 use crate::patch::Patch;
 use crate::table::Table;
 use crate::schema::Schema;
@@ -16,8 +17,6 @@ mod postgres;
 pub type Implementation = mysql::MySqlBackend;
 #[cfg(feature = "postgres")]
 pub type Implementation = postgres::PgBackend;
-#[cfg(feature = "sqlite")]
-compile_error!("sqlite is currently not supported");
 
 pub trait Backend: Sized + Clone {
     const QUOTE: char;
@@ -26,6 +25,20 @@ pub trait Backend: Sized + Clone {
 
     type Bindings: Iterator<Item = Cow<'static, str>> + Default;
 
+    /// Whether `name` has to be quoted to be used as an identifier.
+    fn needs_quote(name: &str) -> bool {
+        !is_plain_ident(name) || Self::RESERVED_IDENTS.contains(&&*name.to_uppercase())
+    }
+
+    /// Quotes `name`, if it has to be or `always` is set.
+    fn quote(name: &str, always: bool) -> Cow<'_, str> {
+        if always || Self::needs_quote(name) {
+            format!("{}{}{}", Self::QUOTE, name, Self::QUOTE).into()
+        } else {
+            Cow::Borrowed(name)
+        }
+    }
+
     /// Generate an `impl <Table>` block, containing getter methods
     fn impl_getters(table: &Table<Self>) -> TokenStream {
         common::getters::<Self>(table)
@@ -36,6 +49,11 @@ pub trait Backend: Sized + Clone {
         common::setters::<Self>(table)
     }
 
+    /// Generate an `impl <Table>` block, containing relation accessors
+    fn impl_relations(table: &Table<Self>) -> TokenStream {
+        common::relations::<Self>(table)
+    }
+
     /// Generate an `impl Table for <Table>` block
     fn impl_table(table: &Table<Self>) -> TokenStream {
         common::impl_table::<Self>(table)
@@ -64,4 +82,15 @@ pub trait Backend: Sized + Clone {
     fn impl_patch(patch: &Patch) -> TokenStream {
         common::impl_patch::<Self>(patch)
     }
+
+    /// Implement the sqlx `Type`, `Encode` and `Decode` traits for an enum
+    fn impl_enum(e: &Enum) -> TokenStream {
+        common::impl_enum::<Self>(e)
+    }
+}
+
+/// whether `name` only consists of letters, digits and underscores, not starting with a digit.
+pub fn is_plain_ident(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
@@ -55,76 +55,113 @@ pub fn impl_insert(table: &Table<MySqlBackend>) -> TokenStream {
     }
 }
 
+/// timestamps filled with the application clock.
+fn now_fields(table: &Table<MySqlBackend>) -> impl Iterator<Item = &TableField<MySqlBackend>> {
+    table.timestamp_fields().filter(|f| f.timestamp_expr().is_none())
+}
+
+/// fields generated by the database, except the ID.
+fn generated_fields(table: &Table<MySqlBackend>) -> impl Iterator<Item = &TableField<MySqlBackend>> {
+    table
+        .default_fields()
+        .filter(|f| !table.is_id(f))
+        .chain(table.timestamp_fields().filter(|f| f.timestamp_expr().is_some()))
+}
+
+/// the id column generated by the database, if any.
+fn generated_id(table: &Table<MySqlBackend>) -> Option<&TableField<MySqlBackend>> {
+    table.id.iter().find(|field| field.default)
+}
+
 /// build an instance of the table struct from
 /// - `_id` (see `query_id` below)
+/// - the timestamps taken in `insert_with`
 /// - `_generated` (see `query_default` below)
 /// - all fields already present in the insert struct
 fn construct_row(table: &Table<MySqlBackend>) -> TokenStream {
-    let id_ident = &table.id.field;
+    let generated_id = generated_id(table).map(|id| {
+        let id_ident = &id.field;
+        quote!(#id_ident: _id as _,)
+    });
     let insert_field_idents = table
         .insertable_fields()
-        .map(|f| &f.field)
-        .filter(|f| *f != id_ident);
-    let default_field_idents = table
-        .default_fields()
-        .map(|f| &f.field)
-        .filter(|f| *f != id_ident);
+        .map(|f| &f.field);
+    let now_field_idents = now_fields(table).map(|f| &f.field);
+    let default_field_idents = generated_fields(table).map(|f| &f.field);
     let unmapped_field_idents = table
         .unmapped_fields()
-        .map(|f| &f.field)
-        .filter(|f| *f != id_ident);
+        .map(|f| &f.field);
 
 
     quote! {
         Self::Table {
-            #id_ident: _id as _,
+            #generated_id
             #( #insert_field_idents: self.#insert_field_idents, )*
+            #( #now_field_idents, )*
             #( #default_field_idents: _generated.#default_field_idents, )*
             #( #unmapped_field_idents: Default::default(), )*
         }
     }
 }
 
-/// queries default fields from the database, except the ID.
+/// queries default fields and database filled timestamps from the database, except the ID.
 fn query_default(table: &Table<MySqlBackend>) -> TokenStream {
-    let mut default_fields = table
-        .default_fields()
-        .filter(|f| f.field != table.id.field)
-        .peekable();
+    let mut default_fields = generated_fields(table).peekable();
 
     if default_fields.peek().is_none() {
         return quote!();
     }
 
     let query_default_sql = format!(
-        "SELECT {} FROM {} WHERE {} = ?",
+        "SELECT {} FROM {} WHERE {}",
         default_fields.map(TableField::fmt_for_select).join(", "),
-        table.table,
-        table.id.column()
+        table.table_name(),
+        table.id_condition(&mut MySqlBindings)
     );
+    let id_arguments = table.id.iter().map(|id| {
+        let id_ident = &id.field;
+        match id.default {
+            true => quote!(_id),
+            false => quote!(self.#id_ident),
+        }
+    });
 
     quote! {
-        let _generated = sqlx::query!(#query_default_sql, _id)
+        let _generated = sqlx::query!(#query_default_sql, #( #id_arguments, )*)
             .fetch_one(db)
             .await?;
     }
 }
 
 
-/// inserts the struct into the database
+/// inserts the struct into the database, taking the current time for timestamps
 fn insert_with(table: &Table<MySqlBackend>) -> TokenStream {
     let insert_fields: Vec<_> = table.insertable_fields().collect();
-    let insert_field_idents = insert_fields.iter().map(|field| &field.field);
-
+    let insert_field_arguments = insert_fields.iter().map(|field| {
+        let ident = &field.field;
+        field.argument(quote!(self.#ident))
+    });
+    let timestamp_fields: Vec<_> = table.timestamp_fields().collect();
+    let now_fields: Vec<_> = now_fields(table).collect();
+    let now_field_idents = now_fields.iter().map(|field| &field.field);
+    let now_field_values = now_fields.iter().map(|field| field.now());
+
+    let mut columns = insert_fields.iter().chain(&timestamp_fields).map(|field| field.column());
+    let mut holders = insert_fields
+        .iter()
+        .map(|_| "?")
+        .chain(timestamp_fields.iter().map(|field| field.timestamp_expr().unwrap_or("?")));
     let insert_sql = format!(
         "INSERT INTO {} ({}) VALUES ({})",
-        table.table,
-        insert_fields.iter().map(|field| field.column()).join(", "),
-        MySqlBindings.take(insert_fields.len()).join(", ")
+        table.table_name(),
+        columns.join(", "),
+        holders.join(", ")
     );
+    let now_arguments = now_fields.iter().map(|field| &field.field);
 
     quote! {
-        sqlx::query!(#insert_sql, #( self.#insert_field_idents, )*)
+        #( let #now_field_idents = #now_field_values; )*
+        sqlx::query!(#insert_sql, #( #insert_field_arguments, )* #( #now_arguments, )*)
             .execute(db as &mut sqlx::MySqlConnection)
             .await?;
     }
@@ -133,20 +170,17 @@ fn insert_with(table: &Table<MySqlBackend>) -> TokenStream {
 /// obtains the id of the inserted row.
 ///
 /// case 1:
-///     The ID is database generated, so we query it with LAST_INSERT_ID
+///     An ID column is database generated, so we query it with LAST_INSERT_ID
 /// case 2:
-///     The ID is already known, so we can just use it.
+///     All ID columns are already known, they are part of the insert struct.
 fn query_id(table: &Table<MySqlBackend>) -> TokenStream {
-    match table.id.default {
-        true => quote! {
+    match generated_id(table) {
+        Some(_) => quote! {
             let _id = sqlx::query!("SELECT LAST_INSERT_ID() AS id")
                 .fetch_one(db as &mut sqlx::MySqlConnection)
                 .await?
                 .id;
         },
-        false => {
-            let id_ident = &table.id.field;
-            quote!(let _id = self.#id_ident;)
-        }
+        None => quote!(),
     }
 }
@@ -11,8 +11,15 @@ mod insert;
 pub struct MySqlBackend;
 
 impl Backend for MySqlBackend {
+    #[cfg(not(feature = "sqlite"))]
     const QUOTE: char = '`';
-    const RESERVED_IDENTS: &'static [&'static str] = &[];
+    #[cfg(not(feature = "sqlite"))]
+    const RESERVED_IDENTS: &'static [&'static str] = include!("../reserved/mysql.rs");
+    /// sqlite shares the `?` bindings of this backend, but quotes like the standard.
+    #[cfg(feature = "sqlite")]
+    const QUOTE: char = '"';
+    #[cfg(feature = "sqlite")]
+    const RESERVED_IDENTS: &'static [&'static str] = include!("../reserved/sqlite.rs");
     type Bindings = MySqlBindings;
 
     fn impl_insert(table: &Table<Self>) -> TokenStream {
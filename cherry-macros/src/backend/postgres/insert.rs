@@ -7,26 +7,45 @@ use crate::backend::postgres::{PgBackend, PgBindings};
 use crate::table::{Table, TableField};
 
 fn insert_sql(table: &Table<PgBackend>, insert_fields: &[&TableField<PgBackend>]) -> String {
-    let columns = insert_fields.iter().map(|field| field.column()).join(", ");
-    let fields = PgBindings::default().take(insert_fields.len()).join(", ");
-    let returning_fields = table
-        .default_fields()
+    let timestamp_fields: Vec<_> = table.timestamp_fields().collect();
+    let columns = insert_fields
+        .iter()
+        .chain(&timestamp_fields)
+        .map(|field| field.column())
+        .join(", ");
+    let mut bindings = PgBindings::default();
+    let fields = insert_fields
+        .iter()
+        .chain(&timestamp_fields)
+        .map(|field| match field.timestamp_expr() {
+            Some(expr) => expr.into(),
+            None => bindings.next().unwrap(),
+        })
+        .join(", ");
+    let returning_fields = generated_fields(table)
         .map(TableField::fmt_for_select)
         .join(", ");
 
     if returning_fields.is_empty() {
         format!(
             "INSERT INTO {} ({}) VALUES ({})",
-            table.table, columns, fields
+            table.table_name(), columns, fields
         )
     } else {
         format!(
             "INSERT INTO {} ({}) VALUES ({}) RETURNING {}",
-            table.table, columns, fields, returning_fields
+            table.table_name(), columns, fields, returning_fields
         )
     }
 }
 
+/// fields generated by the database, including timestamps filled with a database expression.
+fn generated_fields(table: &Table<PgBackend>) -> impl Iterator<Item = &TableField<PgBackend>> {
+    table
+        .default_fields()
+        .chain(table.timestamp_fields().filter(|f| f.timestamp_expr().is_some()))
+}
+
 pub fn impl_insert(table: &Table<PgBackend>) -> TokenStream {
     let insert_ident = match &table.insertable {
         Some(i) => &i.ident,
@@ -34,7 +53,11 @@ pub fn impl_insert(table: &Table<PgBackend>) -> TokenStream {
     };
 
     let insert_fields: Vec<&TableField<PgBackend>> = table.insertable_fields().collect();
-    let default_fields: Vec<&TableField<PgBackend>> = table.default_fields().collect();
+    let default_fields: Vec<&TableField<PgBackend>> = generated_fields(table).collect();
+    let now_fields: Vec<&TableField<PgBackend>> = table
+        .timestamp_fields()
+        .filter(|field| field.timestamp_expr().is_none())
+        .collect();
     let unmapped_fields: Vec<&TableField<PgBackend>> = table.unmapped_fields().collect();
 
 
@@ -47,6 +70,11 @@ pub fn impl_insert(table: &Table<PgBackend>) -> TokenStream {
         .iter()
         .map(|field| &field.field)
         .collect::<Vec<&Ident>>();
+    let now_field_idents = now_fields
+        .iter()
+        .map(|field| &field.field)
+        .collect::<Vec<&Ident>>();
+    let now_field_values = now_fields.iter().map(|field| field.now());
     let unmapped_field_idents = unmapped_fields
         .iter()
         .map(|field| &field.field)
@@ -58,11 +86,7 @@ pub fn impl_insert(table: &Table<PgBackend>) -> TokenStream {
         .iter()
         .map(|field| {
             let ident = &field.field;
-            let ty = &field.ty;
-            match field.custom_type {
-                true => quote!(self.#ident as #ty),
-                false => quote!(self.#ident),
-            }
+            field.argument(quote!(self.#ident))
         })
         .collect::<Vec<TokenStream>>();
 
@@ -84,12 +108,23 @@ pub fn impl_insert(table: &Table<PgBackend>) -> TokenStream {
                     let pool = #table_ident::pool()?;
                     let mut conn = pool.acquire().await?;
 
-                    let _generated = sqlx::query!(#insert_sql, #( #insert_field_exprs, )*)
-                        .#fetch_funtion(&mut *conndb as &mut sqlx::PgConnection)
+                    Ok(self.insert_with(&mut conn).await?)
+                })
+            }
+
+            fn insert_with(
+                self,
+                db: &mut sqlx::PgConnection,
+            ) -> #box_future<sqlx::Result<Self::Table>> {
+                Box::pin(async move {
+                    #( let #now_field_idents = #now_field_values; )*
+                    let _generated = sqlx::query!(#insert_sql, #( #insert_field_exprs, )* #( #now_field_idents, )*)
+                        .#fetch_funtion(db)
                         .await?;
 
                     Ok(Self::Table {
                         #( #insert_field_idents: self.#insert_field_idents, )*
+                        #( #now_field_idents, )*
                         #( #default_field_idents: _generated.#default_field_idents, )*
                         #( #unmapped_field_idents: Default::default(), )*
                     })
@@ -2,7 +2,7 @@ use std::borrow::Cow;
 
 use proc_macro2::TokenStream;
 
-use crate::backend::Backend;
+use crate::backend::{is_plain_ident, Backend};
 use crate::table::Table;
 
 mod insert;
@@ -12,23 +12,16 @@ pub struct PgBackend;
 
 impl Backend for PgBackend {
     const QUOTE: char = '"';
-    #[rustfmt::skip]
-    const RESERVED_IDENTS: &'static [&'static str] = &[
-        "ALL", "ANALYSE", "ANALYZE", "AND", "ANY", "ARRAY", "AS", "ASC", "ASYMMETRIC",
-        "AUTHORIZATION", "BETWEEN", "BINARY", "BOTH", "CASE", "CAST", "CHECK", "COLLATE", "COLUMN",
-        "CONSTRAINT", "CREATE", "CROSS", "CURRENT_DATE", "CURRENT_ROLE", "CURRENT_TIME",
-        "CURRENT_TIMESTAMP", "CURRENT_USER", "DEFAULT", "DEFERRABLE", "DESC", "DISTINCT", "DO",
-        "ELSE", "END", "EXCEPT", "FALSE", "FETCH", "FOR", "FOREIGN", "FROM", "FREEZE", "FULL",
-        "GRANT", "GROUP", "HAVING", "ILIKE", "IN", "INITIALLY", "INNER", "INTERSECT", "INTO", "IS",
-        "ISNULL", "JOIN", "LATERAL", "LEADING", "LEFT", "LIMIT", "LIKE", "LOCALTIME",
-        "LOCALTIMESTAMP", "NATURAL", "NOT", "NOTNULL", "NULL", "OFFSET", "ON", "ONLY", "OR",
-        "ORDER", "OUTER", "OVERLAPS", "PLACING", "PRIMARY", "REFERENCES", "RETURNING", "RIGHT",
-        "SELECT", "SESSION_USER", "SIMILAR", "SOME", "SYMMETRIC", "TABLE", "TABLESAMPLE", "THEN",
-        "TO", "TRAILING", "TRUE", "UNION", "UNIQUE", "USER", "USING", "VARIADIC", "VERBOSE", "WHEN",
-        "WHERE", "WINDOW", "WITH"
-    ];
+    const RESERVED_IDENTS: &'static [&'static str] = include!("../reserved/postgres.rs");
     type Bindings = PgBindings;
 
+    /// Unquoted identifiers are folded to lower case by postgres.
+    fn needs_quote(name: &str) -> bool {
+        !is_plain_ident(name)
+            || name.chars().any(|c| c.is_ascii_uppercase())
+            || Self::RESERVED_IDENTS.contains(&&*name.to_uppercase())
+    }
+
     fn impl_insert(table: &Table<Self>) -> TokenStream {
         insert::impl_insert(table)
     }
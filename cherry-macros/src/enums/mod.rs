@@ -0,0 +1,33 @@
+use std::convert::TryFrom;
+
+use proc_macro2::TokenStream;
+use syn::{DeriveInput, Ident, Result, Type};
+
+use crate::backend::{Backend, Implementation};
+
+mod parse;
+
+pub struct Enum {
+    pub ident: Ident,
+    pub repr: Repr,
+    pub variants: Vec<EnumVariant>,
+}
+
+pub enum Repr {
+    // the name of each variant
+    Text,
+    // the discriminant of each variant, as the given integer type
+    Int(Box<Type>),
+    // the name of each variant, as a native postgres enum of the given type, postgres only
+    Native(String),
+}
+
+pub struct EnumVariant {
+    pub ident: Ident,
+    pub name: String,
+}
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let parsed = Enum::try_from(&input)?;
+    Ok(Implementation::impl_enum(&parsed))
+}
@@ -0,0 +1,63 @@
+use std::convert::TryFrom;
+
+use syn::{Data, DeriveInput, Error, Fields, Result, Variant};
+
+use crate::attrs::{parse_attrs, EnumAttr, EnumVariantAttr};
+use crate::utils::set_once;
+
+use super::{Enum, EnumVariant, Repr};
+
+impl TryFrom<&syn::DeriveInput> for Enum {
+    type Error = Error;
+
+    fn try_from(value: &DeriveInput) -> Result<Self> {
+        let data = match &value.data {
+            Data::Enum(e) => e,
+            _ => return Err(Error::new(value.ident.span(), "CherryEnum can only be derived for enums")),
+        };
+
+        let variants = data
+            .variants
+            .iter()
+            .map(EnumVariant::try_from)
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut repr = None;
+        let (attrs, _other_attrs) = parse_attrs::<EnumAttr>(&value.attrs)?;
+        for attr in attrs {
+            match attr {
+                EnumAttr::Repr(x) => set_once(&mut repr, Repr::Int(x))?,
+                EnumAttr::TypeName(x) => set_once(&mut repr, Repr::Native(x))?,
+            }
+        }
+
+        Ok(Enum {
+            ident: value.ident.clone(),
+            repr: repr.unwrap_or(Repr::Text),
+            variants,
+        })
+    }
+}
+
+impl TryFrom<&syn::Variant> for EnumVariant {
+    type Error = Error;
+
+    fn try_from(value: &Variant) -> Result<Self> {
+        if !matches!(value.fields, Fields::Unit) {
+            return Err(Error::new(value.ident.span(), "CherryEnum variants can not have fields"));
+        }
+
+        let mut rename = None;
+        let (attrs, _other_attrs) = parse_attrs::<EnumVariantAttr>(&value.attrs)?;
+        for attr in attrs {
+            match attr {
+                EnumVariantAttr::Rename(x) => set_once(&mut rename, x)?,
+            }
+        }
+
+        Ok(EnumVariant {
+            ident: value.ident.clone(),
+            name: rename.unwrap_or_else(|| value.ident.to_string()),
+        })
+    }
+}
@@ -2,6 +2,7 @@
 
 mod attrs;
 mod backend;
+mod enums;
 mod patch;
 mod table;
 mod schema;
@@ -34,9 +35,69 @@ mod utils;
 /// This is a central requirement of cherry, and if your table does not fulfill this requirement, cherry
 /// is not what you are looking for.
 ///
+/// Tables keyed by more than one column can list all of them: `#[cherry(id(tenant_id, id))]`.
+/// `Table::Id` then becomes a tuple of the field types, in the listed order, and every generated
+/// query filters on all of the key columns.
+/// A [Patch](trait.Patch.html) of such a table lists the key columns the same way:
+/// `#[cherry(id("tenant_id", "id"))]`.
+///
+/// # Column names
+/// By default, the column of a field has the name of the field. It can be changed for a single
+/// field with `#[cherry(column = "..")]`, or for all fields without it with
+/// `#[cherry(rename_all = "camelCase")]` (or `"snake_case"`, `"SCREAMING_SNAKE_CASE"`).
+///
+/// With `#[cherry(columns)]`, every mapped field also gets a constant naming its column, e.g.
+/// `User::FIRST_NAME`, which can be passed to the query builders instead of a string, and to
+/// [Tracked::set](struct.Tracked.html#method.set).
+///
+/// # Quoting
+/// Table and column names which are reserved words of the database, like `order` or `key`, are
+/// quoted in the generated SQL and by the query builders, as are names postgres would otherwise
+/// fold to lower case. With `#[cherry(always_quote)]`, all of them are quoted.
+///
 /// # CRUD
 /// See the documentation of [Table](trait.Table.html)
 ///
+/// # Soft delete
+/// With `#[cherry(soft_delete = "deleted_at")]`, deleting a row sets the given column to the
+/// current timestamp instead of removing it, and rows with a non-null `deleted_at` are skipped
+/// by `get`, `all`, the getters and [Select](struct.Select.html), unless `with_deleted()` or
+/// `only_deleted()` is called on it.
+/// Rows can be brought back with `restore`, or removed for good with `force_delete`.
+///
+/// # Timestamps
+/// Fields annotated with `#[cherry(created_at)]` or `#[cherry(updated_at)]` are filled with the
+/// current time when inserting, and `updated_at` again on every update, including patches and
+/// `update` queries of the datasource.
+/// The time is taken from the application clock, so the field type has to implement
+/// [Timestamp](trait.Timestamp.html) (the `chrono` and `time` types do with the matching feature).
+/// To let the database fill it instead, give the expression: `#[cherry(updated_at = "NOW()")]`.
+/// Timestamps are not part of the generated insertable struct.
+///
+/// # Optimistic locking
+/// With an integer field annotated with `#[cherry(version)]`, `update`, `update_with` and `patch`
+/// only update the row if its version is still the one in memory, and increment it.
+/// If the row was changed in the meantime, they fail with a
+/// [StaleObject](error/struct.StaleObject.html) error, which can be recognized with
+/// `StaleObject::is(&err)`.
+///
+/// # Hooks
+/// With `#[cherry(hooks)]`, the struct has to implement [Hooks](trait.Hooks.html), whose methods
+/// are run around inserts through the datasource, `update`, `update_with`, `delete` and
+/// `delete_with`. A [Patch](trait.Patch.html) can opt in the same way to run its update hooks.
+///
+/// # JSON
+/// Fields annotated with `#[cherry(json)]` are stored as JSON, so any type implementing serde's
+/// `Serialize` and `Deserialize` can be used for them. This requires the `json` feature.
+/// Rows of such a table are decoded at runtime, since `query_as!` can not decode these fields.
+/// Values inside of them can be matched with `and_where_json_eq("settings", "$.theme", "dark")`
+/// on [Select](struct.Select.html).
+///
+/// # Converters
+/// A field annotated with `#[cherry(with = "path::to::module")]` is stored through the functions
+/// `to_db(&T) -> D` and `from_db(D) -> Result<T, E>` of that module, where `D` is a type of the
+/// database, e.g. to encrypt or compress the value.
+///
 /// # Insertable
 /// cherry will generate a helper struct for inserting rows into the database when using
 /// `#[cherry(insertable)]`.
@@ -83,11 +144,41 @@ mod utils;
 /// By default, the function will be named `set_{field_name)`, though this can be changed by
 /// supplying a custom name: `#[cherry(set = set_name)]`.
 ///
+/// # Relations
+/// cherry will generate accessor functions for relations declared on the struct with
+/// `#[cherry(has_many(Book, foreign_key = "user_id"))]`, `#[cherry(has_one(..))]` and
+/// `#[cherry(belongs_to(User, foreign_key = "user_id"))]`.
+/// Each accessor returns a [Select](struct.Select.html) of the related table, already filtered
+/// on the foreign key, so it can be narrowed further before fetching it.
+///
+/// The generated functions will have these signature:
+/// **`#[cherry(has_many(Book, ..))]`**:
+/// `{pub} fn books<'a>(&self) -> Select<'a, Book>`, matching `user_id` against the ID of `self`.
+///
+/// **`#[cherry(has_one(Profile, ..))]`**:
+/// `{pub} fn profile<'a>(&self) -> Select<'a, Profile>`, matching `user_id` against the ID of `self`.
+///
+/// **`#[cherry(belongs_to(User, ..))]`**:
+/// `{pub} fn user<'a>(&self) -> Select<'a, User>`, matching the ID of `User` against the
+/// `user_id` field of `self`.
+///
+/// If the target of `belongs_to` has a composite id, give a foreign key per id column, in the order
+/// of its id: `#[cherry(belongs_to(Membership, foreign_key("tenant_id", "membership_id")))]`.
+/// A foreign key count that does not match the target fails to compile.
+///
+/// By default, the function is named after the related type, pluralized for `has_many`
+/// (`Category` gives `categories`), though this can be changed by supplying a custom name:
+/// `#[cherry(has_many(Book, foreign_key = "user_id", name = novels))]`.
+///
 /// # Custom types
 /// When using custom types (which implement `sqlx::Type`), the field has to annotated with
 /// `#[cherry(custom_type)]`.
 /// This will use a column type override for querying this field
 /// (see [the sqlx docs on this](https://docs.rs/sqlx/0.4.0-beta.1/sqlx/macro.query_as.html#column-type-override-infer-from-struct-field)).
+///
+/// Without the annotation, fields of types the sqlx macros do not know, like a
+/// [CherryEnum](derive.CherryEnum.html), are bound with an `as _` override and the rows are
+/// decoded at runtime, as for `#[cherry(json)]` fields.
 #[proc_macro_error::proc_macro_error]
 #[proc_macro_derive(Table, attributes(cherry))]
 pub fn derive_table(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -106,6 +197,11 @@ pub fn derive_table(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 }
 
 /// Derives [Patch](trait.Patch.html).
+///
+/// The patch is applied through the [Schema](trait.Schema.html) of `#[cherry(table = ..)]`,
+/// so its table name and `#[cherry(updated_at)]` column are taken from there.
+/// The values are bound at runtime, `#[cherry(table_name = ..)]` and `#[cherry(custom_type)]`
+/// are rejected.
 #[proc_macro_error::proc_macro_error]
 #[proc_macro_derive(Patch, attributes(cherry))]
 pub fn derive_patch(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -116,3 +212,33 @@ pub fn derive_patch(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     }
     .into()
 }
+
+/// Derives the sqlx `Type`, `Encode` and `Decode` traits for an enum without fields, so it can be
+/// used as a column of a [Table](trait.Table.html) without `#[cherry(custom_type)]`.
+///
+/// By default, each variant is stored as text, using its name or `#[cherry(rename = "..")]`.
+///
+/// With `#[cherry(repr = i32)]`, variants are stored as their discriminant, using the given
+/// integer type.
+///
+/// With `#[cherry(type_name = "..")]`, variants are stored as the given native postgres enum type.
+///
+/// ```rust,ignore
+/// #[derive(cherry::CherryEnum)]
+/// enum Status {
+///     #[cherry(rename = "active")]
+///     Active,
+///     #[cherry(rename = "banned")]
+///     Banned,
+/// }
+/// ```
+#[proc_macro_error::proc_macro_error]
+#[proc_macro_derive(CherryEnum, attributes(cherry))]
+pub fn derive_enum(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    match enums::derive(input) {
+        Ok(ok) => ok,
+        Err(err) => err.to_compile_error(),
+    }
+    .into()
+}
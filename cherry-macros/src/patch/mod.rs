@@ -1,7 +1,7 @@
 use std::convert::TryFrom;
 
 use proc_macro2::TokenStream;
-use syn::{DeriveInput, Ident, Path, Result, Type};
+use syn::{DeriveInput, Ident, Path, Result};
 
 use crate::backend::{Backend, Implementation};
 
@@ -9,20 +9,43 @@ mod parse;
 
 pub struct Patch {
     pub ident: Ident,
-    pub table_name: String,
     pub table: Path,
-    pub id: String,
+    pub id: Vec<String>,
     pub fields: Vec<PatchField>,
+    pub hooks: bool,
 }
 
 pub struct PatchField {
     pub ident: Ident,
     pub column: String,
-    pub ty: Type,
-    pub custom_type: bool
 }
 
 pub fn derive(input: DeriveInput) -> Result<TokenStream> {
     let parsed = Patch::try_from(&input)?;
     Ok(Implementation::impl_patch(&parsed))
 }
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    #[test]
+    fn composite_ids_are_matched_column_by_column() {
+        let tokens = derive(parse_quote! {
+            #[cherry(table = Membership, id("tenant_id", "user_id"))]
+            struct RenameRole {
+                role: String,
+            }
+        })
+        .unwrap()
+        .to_string()
+        .replace(' ', "");
+        assert!(
+            tokens.contains(r#".set("role",&self.role).and_where_eq("tenant_id",id.0).and_where_eq("user_id",id.1)"#),
+            "{}",
+            tokens
+        );
+    }
+}
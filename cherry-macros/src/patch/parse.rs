@@ -1,5 +1,6 @@
 use std::convert::TryFrom;
 
+use proc_macro2::Span;
 use syn::{Data, DeriveInput, Error, Field, Result};
 
 use crate::attrs::{parse_attrs, PatchAttr, PatchFieldAttr};
@@ -24,23 +25,32 @@ impl TryFrom<&syn::DeriveInput> for Patch {
             .collect::<Result<Vec<_>>>()?;
 
         let mut table = None;
-        let mut table_name = None;
         let mut id = None;
+        let mut hooks = None;
         let (attrs,_other_attrs) = parse_attrs::<PatchAttr>(&value.attrs)?;
         for attr in attrs {
             match attr {
                 PatchAttr::Table(x) => set_once(&mut table, x)?,
-                PatchAttr::TableName(x) => set_once(&mut table_name, x)?,
+                PatchAttr::TableName(name) => {
+                    return Err(Error::new(
+                        Span::call_site(),
+                        format!(
+                            "#[cherry(table_name = \"{}\")] is no longer supported, the table name is taken from #[cherry(table = ..)]",
+                            name,
+                        ),
+                    ))
+                }
                 PatchAttr::Id(x) => set_once(&mut id, x)?,
+                PatchAttr::Hooks(..) => set_once(&mut hooks, true)?,
             }
         }
 
         Ok(Patch {
             ident: value.ident.clone(),
-            table_name: table_name.ok_or_else(|| missing_attr("table_name"))?,
             table: table.ok_or_else(|| missing_attr("table"))?,
-            id: id.ok_or_else(|| missing_attr("id"))?,
+            id: id.ok_or_else(|| missing_attr("id"))?.columns,
             fields,
+            hooks: hooks.unwrap_or(false),
         })
     }
 }
@@ -52,20 +62,22 @@ impl TryFrom<&syn::Field> for PatchField {
         let ident = value.ident.clone().unwrap();
 
         let mut column = None;
-        let mut custom_type = None;
         let (attrs,_other_attrs) = parse_attrs::<PatchFieldAttr>(&value.attrs)?;
         for attr in attrs {
             match attr {
                 PatchFieldAttr::Column(x) => set_once(&mut column, x)?,
-                PatchFieldAttr::CustomType(_) => set_once(&mut custom_type, true)?,
+                PatchFieldAttr::CustomType(..) => {
+                    return Err(Error::new(
+                        ident.span(),
+                        "#[cherry(custom_type)] is not needed on patches, their values are bound at runtime",
+                    ))
+                }
             }
         }
 
         Ok(PatchField {
             ident: value.ident.clone().unwrap(),
             column: column.unwrap_or_else(|| ident.to_string()),
-            ty: value.ty.clone(),
-            custom_type: custom_type.unwrap_or(false),
         })
     }
 }
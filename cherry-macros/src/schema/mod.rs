@@ -17,6 +17,13 @@ pub struct Schema<B: Backend> {
     pub fields: Vec<TableField<B>>,
     pub queryable: Option<Queryable>,
     pub datasource: Ident,
+    pub soft_delete: Option<String>,
+    pub hooks: bool,
+    pub always_quote: bool,
+    pub schema: Option<String>,
+    pub tenant_column: Option<String>,
+    /// whether to generate a `cherry::Column` constant per field.
+    pub columns: bool,
 }
 
 
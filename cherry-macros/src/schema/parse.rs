@@ -22,7 +22,7 @@ impl<B: Backend> TryFrom<&syn::DeriveInput> for Schema<B> {
             _ => panic!("not a struct with named fields"),
         };
 
-        let fields = data
+        let mut fields = data
             .fields
             .iter()
             .map(TableField::try_from)
@@ -30,7 +30,7 @@ impl<B: Backend> TryFrom<&syn::DeriveInput> for Schema<B> {
 
         // fields.retain(|field| !field.unmapped);
 
-        none!(table, datasource, queryable);
+        none!(table, datasource, queryable, soft_delete, hooks, rename_all, always_quote, schema, tenant_column, columns);
         let (attrs, _other_attrs) = parse_attrs::<TableAttr>(&value.attrs)?;
         for attr in attrs {
             match attr {
@@ -43,12 +43,46 @@ impl<B: Backend> TryFrom<&syn::DeriveInput> for Schema<B> {
                     };
                     set_once(&mut queryable, x.unwrap_or_else(default))?;
                 }
+                TableAttr::SoftDelete(x) => set_once(&mut soft_delete, x)?,
+                TableAttr::Hooks(..) => set_once(&mut hooks, true)?,
+                TableAttr::RenameAll(x) => set_once(&mut rename_all, x)?,
+                TableAttr::AlwaysQuote(..) => set_once(&mut always_quote, true)?,
+                TableAttr::Schema(x) => set_once(&mut schema, x)?,
+                TableAttr::TenantColumn(x) => set_once(&mut tenant_column, x)?,
+                TableAttr::Columns(..) => set_once(&mut columns, true)?,
                 _ => {}
             }
         }
 
+        if let Some(rename_all) = rename_all {
+            fields.iter_mut().for_each(|field| field.rename(rename_all));
+        }
+        let always_quote = always_quote.unwrap_or(false);
+        if always_quote {
+            fields.iter_mut().for_each(|field| field.quoted = true);
+        }
+
         let datasource = datasource.ok_or_else(|| missing_attr("datasource"))?;
 
+        if let Some(column) = &soft_delete {
+            if !fields.iter().any(|field| !field.unmapped && field.column_name == *column) {
+                return Err(Error::new(
+                    Span::call_site(),
+                    format!("soft_delete \"{}\" does not refer to a column of the struct", column),
+                ));
+            }
+        }
+
+        if let Some(column) = &tenant_column {
+            if !fields.iter().any(|field| {
+                !field.unmapped && field.timestamp.is_none() && field.column_name == *column
+            }) {
+                return Err(Error::new(
+                    Span::call_site(),
+                    format!("tenant_column \"{}\" does not refer to a column of the struct", column),
+                ));
+            }
+        }
 
         //@TODO add any checks for query settings without queryable
 
@@ -59,6 +93,12 @@ impl<B: Backend> TryFrom<&syn::DeriveInput> for Schema<B> {
             queryable,
             fields,
             datasource,
+            soft_delete,
+            hooks: hooks.unwrap_or(false),
+            always_quote,
+            schema,
+            tenant_column,
+            columns: columns.unwrap_or(false),
         })
     }
 }
@@ -3,9 +3,10 @@ use std::convert::TryFrom;
 use itertools::Itertools;
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
+use syn::ext::IdentExt;
 use syn::{DeriveInput, Result, Type, Visibility};
 
-use crate::attrs::{Getter, Insertable};
+use crate::attrs::{Getter, Insertable, Relation, RenameAll};
 use crate::backend::{Backend, Implementation};
 use std::borrow::Cow;
 use std::marker::PhantomData;
@@ -16,9 +17,16 @@ pub struct Table<B: Backend> {
     pub ident: Ident,
     pub vis: Visibility,
     pub table: String,
-    pub id: TableField<B>,
+    pub id: Vec<TableField<B>>,
     pub fields: Vec<TableField<B>>,
     pub insertable: Option<Insertable>,
+    pub has_many: Vec<Relation>,
+    pub has_one: Vec<Relation>,
+    pub belongs_to: Vec<Relation>,
+    pub soft_delete: Option<TableField<B>>,
+    pub hooks: bool,
+    pub always_quote: bool,
+    pub schema: Option<String>,
 }
 
 #[derive(Clone)]
@@ -26,26 +34,111 @@ pub struct TableField<B: Backend> {
     pub field: Ident,
     pub ty: Type,
     pub column_name: String,
+    pub explicit_column: bool,
     pub custom_type: bool,
     pub unmapped: bool,
-    pub reserved_ident: bool,
+    /// Unmapped, but read from the row when the query selects its column.
+    pub computed: bool,
+    pub quoted: bool,
     pub default: bool,
     pub get_one: Option<Getter>,
     pub get_optional: Option<Getter>,
     pub get_many: Option<Getter>,
     pub set: Option<Ident>,
+    pub timestamp: Option<Timestamp>,
+    pub version: bool,
+    pub json: bool,
+    pub with: Option<syn::Path>,
     pub other_attrs: TokenStream,
     pub _phantom: PhantomData<*const B>,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum TimestampKind {
+    Created,
+    Updated,
+}
+
+/// A `created_at` or `updated_at` column, filled with the application clock
+/// or with a database expression.
+#[derive(Clone)]
+pub struct Timestamp {
+    pub kind: TimestampKind,
+    pub expr: Option<String>,
+}
+
 impl<B: Backend> Table<B> {
     pub fn fields_except_id(&self) -> impl Iterator<Item = &TableField<B>> + Clone {
-        let id = self.id.field.clone();
-        self.mapped_fields().filter(move |field| field.field != id)
+        self.mapped_fields().filter(move |field| !self.is_id(field))
+    }
+
+    /// Name of the table, qualified with its schema and quoted if necessary.
+    pub fn table_name(&self) -> Cow<'_, str> {
+        let table = B::quote(&self.table, self.always_quote);
+        match &self.schema {
+            Some(schema) => format!("{}.{}", B::quote(schema, self.always_quote), table).into(),
+            None => table,
+        }
+    }
+
+    pub fn is_id(&self, field: &TableField<B>) -> bool {
+        self.id.iter().any(|id| id.field == field.field)
+    }
+
+    /// Type of `Table::Id`, a tuple if the id spans multiple columns.
+    pub fn id_ty(&self) -> TokenStream {
+        match self.id.as_slice() {
+            [id] => {
+                let ty = &id.ty;
+                quote!(#ty)
+            }
+            ids => {
+                let tys = ids.iter().map(|id| &id.ty);
+                quote!(( #( #tys, )* ))
+            }
+        }
+    }
+
+    /// Splits a value of `Table::Id` into one expression per id column.
+    pub fn id_arguments(&self, id: &TokenStream) -> Vec<TokenStream> {
+        match self.id.len() {
+            1 => vec![id.clone()],
+            len => (0..len)
+                .map(|i| {
+                    let i = syn::Index::from(i);
+                    quote!(#id.#i)
+                })
+                .collect(),
+        }
+    }
+
+    /// `WHERE` condition matching every id column, taking one binding per column.
+    pub fn id_condition(&self, bindings: &mut B::Bindings) -> String {
+        self.id
+            .iter()
+            .map(|id| format!("{} = {}", id.column(), bindings.next().unwrap()))
+            .join(" AND ")
+    }
+
+    /// `WHERE` condition excluding soft deleted rows, joined to `condition` if there is one.
+    pub fn not_deleted(&self, condition: &str) -> String {
+        match (&self.soft_delete, condition) {
+            (None, condition) => condition.to_string(),
+            (Some(field), "") => format!("{} IS NULL", field.column()),
+            (Some(field), condition) => format!("{} AND {} IS NULL", condition, field.column()),
+        }
     }
 
     pub fn insertable_fields(&self) -> impl Iterator<Item = &TableField<B>> + Clone {
-        self.mapped_fields().filter(|field| !field.default)
+        self.mapped_fields().filter(|field| !field.default && field.timestamp.is_none())
+    }
+
+    pub fn version_field(&self) -> Option<&TableField<B>> {
+        self.mapped_fields().find(|field| field.version)
+    }
+
+    pub fn timestamp_fields(&self) -> impl Iterator<Item = &TableField<B>> + Clone {
+        self.mapped_fields().filter(|field| field.timestamp.is_some())
     }
 
     pub fn default_fields(&self) -> impl Iterator<Item = &TableField<B>> + Clone {
@@ -60,7 +153,32 @@ impl<B: Backend> Table<B> {
         self.fields.iter().filter(|field| field.unmapped)
     }
 
+    /// Whether rows have to be decoded through `Schema::from_row`, since `query_as!` can not
+    /// decode json fields, fields converted `with` a module or types it does not know.
+    pub fn decoded_at_runtime(&self) -> bool {
+        self.mapped_fields().any(|field| field.json || field.with.is_some() || field.unchecked())
+    }
+
+    /// Query mapping the rows of `sql` to `Self`.
+    pub fn query_as(&self, sql: &str, arguments: &[TokenStream]) -> TokenStream {
+        if self.decoded_at_runtime() {
+            quote! {
+                sqlx::query(#sql)
+                    #( .bind(#arguments) )*
+                    .try_map(|row: cherry::types::Row| {
+                        <Self as cherry::Schema>::from_row(&row).map_err(cherry::error::into_sqlx)
+                    })
+            }
+        } else {
+            quote!(sqlx::query_as!(Self, #sql, #( #arguments, )*))
+        }
+    }
+
     pub fn select_column_list(&self) -> String {
+        if self.decoded_at_runtime() {
+            return self.mapped_fields().map(|field| field.column()).join(", ");
+        }
+
         let result_fields = self.mapped_fields()
             .map(|field| field.fmt_for_select())
             .join(", ");
@@ -78,29 +196,103 @@ impl<B: Backend> Table<B> {
 }
 
 impl<B: Backend> TableField<B> {
+    /// Whether the type of this field is unknown to the sqlx macros, like a `CherryEnum`, without
+    /// being marked as `#[cherry(custom_type)]`. Its values are bound with an `as _` override.
+    pub fn unchecked(&self) -> bool {
+        !self.custom_type && !self.json && self.with.is_none() && !checked_type(&self.ty)
+    }
+
     pub fn fmt_for_select(&self) -> String {
-        if self.custom_type {
+        let field = self.field.unraw().to_string();
+        if self.custom_type || self.unchecked() {
             format!(
                 "{} AS {}{}: _{}",
                 self.column(),
                 B::QUOTE,
-                self.field,
+                field,
                 B::QUOTE
             )
-        } else if self.field == self.column_name {
+        } else if field == self.column_name {
             self.column().into()
         } else {
-            format!("{} AS {}", self.column(), self.field)
+            format!("{} AS {}", self.column(), B::quote(&field, false))
         }
     }
 
-    pub fn column(&self) -> Cow<str> {
-        if self.reserved_ident {
-            format!("{}{}{}", B::QUOTE, self.column_name, B::QUOTE).into()
+    /// Argument binding `value` to the column of this field.
+    pub fn argument(&self, value: TokenStream) -> TokenStream {
+        let ty = &self.ty;
+        if self.json {
+            quote!(cherry::sqlx::Json(&#value) as _)
+        } else if let Some(with) = &self.with {
+            quote!(#with::to_db(&#value) as _)
+        } else if self.custom_type {
+            quote!(#value as #ty)
+        } else if self.unchecked() {
+            quote!(#value as _)
         } else {
-            Cow::Borrowed(&self.column_name)
+            value
         }
     }
+
+    pub fn is_timestamp(&self, kind: TimestampKind) -> bool {
+        matches!(&self.timestamp, Some(timestamp) if timestamp.kind == kind)
+    }
+
+    /// The database expression filling this timestamp, if it is not set by the application.
+    pub fn timestamp_expr(&self) -> Option<&str> {
+        self.timestamp.as_ref().and_then(|timestamp| timestamp.expr.as_deref())
+    }
+
+    /// The current time, as a value of this field.
+    pub fn now(&self) -> TokenStream {
+        let ty = &self.ty;
+        quote!(<#ty as cherry::Timestamp>::now())
+    }
+
+    /// Names the column of this field after `rule`, unless it was given with `#[cherry(column = ..)]`.
+    pub fn rename(&mut self, rule: RenameAll) {
+        if !self.explicit_column {
+            self.column_name = rule.apply(&self.field.unraw().to_string());
+            self.quoted = B::needs_quote(&self.column_name);
+        }
+    }
+
+    pub fn column(&self) -> Cow<str> {
+        B::quote(&self.column_name, self.quoted)
+    }
+}
+
+/// Whether the sqlx macros check values of `ty`, which are the types sqlx maps to a column.
+fn checked_type(ty: &Type) -> bool {
+    const TYPES: &[&str] = &[
+        "bool", "i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "f32", "f64", "str",
+        "String", "Vec", "Option", "Box", "Cow", "Duration", "NaiveDate", "NaiveTime",
+        "NaiveDateTime", "DateTime", "Utc", "Local", "FixedOffset", "Date", "Time",
+        "PrimitiveDateTime", "OffsetDateTime", "Uuid", "Decimal", "BigDecimal", "IpNetwork",
+        "Value", "JsonValue",
+    ];
+    match ty {
+        Type::Reference(reference) => checked_type(&reference.elem),
+        Type::Slice(slice) => checked_type(&slice.elem),
+        Type::Paren(paren) => checked_type(&paren.elem),
+        Type::Group(group) => checked_type(&group.elem),
+        Type::Path(path) if path.qself.is_none() => {
+            let segment = path.path.segments.last().unwrap();
+            let arguments = match &segment.arguments {
+                syn::PathArguments::None => true,
+                syn::PathArguments::AngleBracketed(arguments) => {
+                    arguments.args.iter().all(|argument| match argument {
+                        syn::GenericArgument::Type(ty) => checked_type(ty),
+                        _ => true,
+                    })
+                }
+                syn::PathArguments::Parenthesized(_) => false,
+            };
+            arguments && TYPES.contains(&&*segment.ident.to_string())
+        }
+        _ => false,
+    }
 }
 
 impl Getter {
@@ -126,6 +318,7 @@ pub fn derive(input: &DeriveInput) -> Result<TokenStream> {
     let impl_insert = Implementation::impl_insert(&parsed);
     let getters = Implementation::impl_getters(&parsed);
     let setters = Implementation::impl_setters(&parsed);
+    let relations = Implementation::impl_relations(&parsed);
 
     Ok(quote! {
         #impl_table
@@ -133,8 +326,95 @@ pub fn derive(input: &DeriveInput) -> Result<TokenStream> {
         #impl_insert
         #getters
         #setters
+        #relations
     })
 }
 
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn parse(input: DeriveInput) -> Table<Implementation> {
+        Table::try_from(&input).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    #[test]
+    fn composite_ids_are_matched_column_by_column() {
+        let table = parse(parse_quote! {
+            #[cherry(table = "membership", id(tenant_id, user_id), datasource = Db)]
+            struct Membership {
+                tenant_id: i64,
+                user_id: i64,
+                role: String,
+            }
+        });
+        let mut bindings = <Implementation as Backend>::Bindings::default();
+        let condition = table.id_condition(&mut bindings);
+        #[cfg(feature = "postgres")]
+        assert_eq!(condition, "tenant_id = $1 AND user_id = $2");
+        #[cfg(not(feature = "postgres"))]
+        assert_eq!(condition, "tenant_id = ? AND user_id = ?");
+        assert_eq!(table.id_arguments(&quote!(id)).iter().map(ToString::to_string).join(", "), "id . 0, id . 1");
+    }
+
+    #[test]
+    fn enum_fields_are_bound_and_decoded_at_runtime() {
+        let table = parse(parse_quote! {
+            #[cherry(table = "member", id = id, datasource = Db)]
+            struct Member {
+                id: i64,
+                // #[cherry(rename = "..")] variants, stored as text
+                status: Status,
+                // #[cherry(repr = i16)]
+                level: Level,
+                // #[cherry(type_name = "mood")]
+                mood: Option<Mood>,
+            }
+        });
+        assert!(table.decoded_at_runtime());
+        let enums = table.fields.iter().filter(|field| field.unchecked()).map(|field| {
+            let ident = &field.field;
+            field.argument(quote!(self.#ident)).to_string()
+        });
+        assert_eq!(
+            enums.collect::<Vec<_>>(),
+            ["self . status as _", "self . level as _", "self . mood as _"]
+        );
+        assert_eq!(table.fields[0].argument(quote!(self.id)).to_string(), "self . id");
+    }
 
+    #[test]
+    fn fields_of_sqlx_types_are_checked() {
+        let table = parse(parse_quote! {
+            #[cherry(table = "event", id = id, datasource = Db)]
+            struct Event {
+                id: i64,
+                name: String,
+                payload: Vec<u8>,
+                at: Option<chrono::DateTime<chrono::Utc>>,
+                #[cherry(custom_type)]
+                kind: Kind,
+            }
+        });
+        assert!(!table.decoded_at_runtime());
+        assert!(table.fields.iter().all(|field| !field.unchecked()));
+    }
 
+    #[test]
+    fn raw_fields_are_selected_by_their_name() {
+        let table = parse(parse_quote! {
+            #[cherry(table = "thing", id = id, datasource = Db)]
+            struct Thing {
+                id: i64,
+                r#type: String,
+                #[cherry(column = "kind")]
+                r#match: String,
+            }
+        });
+        let quote = |name| <Implementation as Backend>::quote(name, false).into_owned();
+        assert_eq!(table.fields[1].fmt_for_select(), quote("type"));
+        assert_eq!(table.fields[2].fmt_for_select(), format!("kind AS {}", quote("match")));
+    }
+}
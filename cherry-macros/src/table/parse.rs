@@ -1,12 +1,13 @@
 use std::convert::TryFrom;
 
 use proc_macro2::Span;
+use syn::ext::IdentExt;
 use syn::{Data, DeriveInput, Error, Ident, Result};
 
 use crate::attrs::{parse_attrs, Insertable, TableAttr, TableFieldAttr};
 use crate::utils::{missing_attr, set_once};
 
-use super::{Table, TableField};
+use super::{Table, TableField, Timestamp, TimestampKind};
 use crate::backend::Backend;
 use std::marker::PhantomData;
 
@@ -16,20 +17,24 @@ macro_rules! none {
 
 use syn::{ Type};
 
+/// whether `ty` is written as `Option<..>`.
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(typepath) => {
+            typepath.path.leading_colon.is_none()
+                && typepath.path.segments.len() == 1
+                && typepath.path.segments.iter().next().unwrap().ident == "Option"
+        }
+        _ => false,
+    }
+}
+
 impl<B: Backend> TryFrom<&syn::Field> for TableField<B> {
     type Error = Error;
 
     fn try_from(value: &syn::Field) -> Result<Self> {
         let ident = value.ident.clone().unwrap();
 
-        let reserved_ident = B::RESERVED_IDENTS.contains(&&*ident.to_string().to_uppercase());
-        if reserved_ident {
-            proc_macro_error::emit_warning!(
-                ident.span(),
-                "This is a reserved keyword, you might want to consider choosing a different name."
-            );
-        }
-
         none!(
             column,
             custom_type,
@@ -38,7 +43,12 @@ impl<B: Backend> TryFrom<&syn::Field> for TableField<B> {
             get_many,
             set,
             default,
-            unmapped
+            unmapped,
+            computed,
+            timestamp,
+            version,
+            json,
+            with
         );
 
         let (attrs,other_attrs) =parse_attrs::<TableFieldAttr>(&value.attrs)?;
@@ -54,42 +64,92 @@ impl<B: Backend> TryFrom<&syn::Field> for TableField<B> {
                     set_once(&mut set, s.unwrap_or_else(default))?
                 }
                 TableFieldAttr::Default(..) => set_once(&mut default, true)?,
+                TableFieldAttr::CreatedAt(expr) => {
+                    set_once(&mut timestamp, Timestamp { kind: TimestampKind::Created, expr })?
+                }
+                TableFieldAttr::UpdatedAt(expr) => {
+                    set_once(&mut timestamp, Timestamp { kind: TimestampKind::Updated, expr })?
+                }
+                TableFieldAttr::Version(..) => set_once(&mut version, true)?,
+                TableFieldAttr::Json(..) => set_once(&mut json, true)?,
+                TableFieldAttr::With(path) => set_once(&mut with, syn::parse_str::<syn::Path>(&path)?)?,
                 TableFieldAttr::Unmapped(..) => {
-                    let is_option = match &value.ty {
-                        Type::Path(typepath) => {
-                            if typepath.path.leading_colon.is_none()
-                                && typepath.path.segments.len() == 1
-                                && typepath.path.segments.iter().next().unwrap().ident == "Option"
-                            {
-                                set_once(&mut unmapped, true)?;
-                                true
-                            }
-                            else { false }
-                        }
-                        _ => { false }
-                    };
-                    if !is_option {
+                    if !is_option(&value.ty) {
                         return Err(Error::new(
                             Span::call_site(),
                             "#[cherry(unmapped)] fields must be of type Option",
                         ));
                     }
+                    set_once(&mut unmapped, true)?;
+                },
+                TableFieldAttr::Computed(..) => {
+                    if !is_option(&value.ty) {
+                        return Err(Error::new(
+                            ident.span(),
+                            "#[cherry(computed)] fields must be of type Option",
+                        ));
+                    }
+                    set_once(&mut computed, true)?;
                 },
             }
         }
 
+        if json.is_some() && with.is_some() {
+            return Err(Error::new(
+                ident.span(),
+                "#[cherry(json)] and #[cherry(with = ..)] can not be combined",
+            ));
+        }
+
+        if (json.is_some() || with.is_some())
+            && (custom_type.is_some()
+                || version.is_some()
+                || timestamp.is_some()
+                || get_one.is_some()
+                || get_optional.is_some()
+                || get_many.is_some())
+        {
+            return Err(Error::new(
+                ident.span(),
+                "#[cherry(json)] and #[cherry(with = ..)] fields can not be a #[cherry(custom_type)], a timestamp, #[cherry(version)] or have getters",
+            ));
+        }
+
+        if computed.is_some()
+            && (unmapped.is_some()
+                || default.is_some()
+                || version.is_some()
+                || timestamp.is_some()
+                || json.is_some()
+                || with.is_some()
+                || set.is_some())
+        {
+            return Err(Error::new(
+                ident.span(),
+                "#[cherry(computed)] fields are only read, they can not be #[cherry(unmapped)], #[cherry(default)], a timestamp, #[cherry(version)], #[cherry(json)], converted or set",
+            ));
+        }
+
+        let explicit_column = column.is_some();
+        let column_name = column.unwrap_or_else(|| ident.unraw().to_string());
         Ok(TableField {
-            column_name: column.unwrap_or_else(|| ident.to_string()),
+            quoted: B::needs_quote(&column_name),
+            column_name,
+            explicit_column,
             field: ident,
             ty: value.ty.clone(),
             custom_type: custom_type.unwrap_or(false),
-            unmapped: unmapped.unwrap_or(false),
-            reserved_ident,
+            unmapped: unmapped.is_some() || computed.is_some(),
+            computed: computed.unwrap_or(false),
             default: default.unwrap_or(false),
             get_one,
             get_optional,
             get_many,
             set,
+            timestamp,
+            version: version.unwrap_or(false),
+            json: json.unwrap_or(false),
+            with,
             other_attrs,
             _phantom: PhantomData,
         })
@@ -105,13 +165,16 @@ impl<B: Backend> TryFrom<&syn::DeriveInput> for Table<B> {
             _ => panic!("not a struct with named fields"),
         };
 
-        let fields = data
+        let mut fields = data
             .fields
             .iter()
             .map(TableField::try_from)
             .collect::<Result<Vec<_>>>()?;
 
-        none!(table, id, insertable);
+        none!(table, id, insertable, soft_delete, hooks, rename_all, always_quote, schema);
+        let mut has_many = vec![];
+        let mut has_one = vec![];
+        let mut belongs_to = vec![];
         let (attrs, _other_attrs) = parse_attrs::<TableAttr>(&value.attrs)?;
         for attr in attrs {
             match attr {
@@ -124,22 +187,128 @@ impl<B: Backend> TryFrom<&syn::DeriveInput> for Table<B> {
                     };
                     set_once(&mut insertable, x.unwrap_or_else(default))?;
                 },
+                TableAttr::HasMany(x) => has_many.push(x),
+                TableAttr::HasOne(x) => has_one.push(x),
+                TableAttr::BelongsTo(x) => belongs_to.push(x),
+                TableAttr::SoftDelete(x) => set_once(&mut soft_delete, x)?,
+                TableAttr::Hooks(..) => set_once(&mut hooks, true)?,
+                TableAttr::RenameAll(x) => set_once(&mut rename_all, x)?,
+                TableAttr::AlwaysQuote(..) => set_once(&mut always_quote, true)?,
+                TableAttr::Schema(x) => set_once(&mut schema, x)?,
                 _ => {}
             }
         }
 
+        if let Some(rename_all) = rename_all {
+            fields.iter_mut().for_each(|field| field.rename(rename_all));
+        }
+        let always_quote = always_quote.unwrap_or(false);
+        if always_quote {
+            fields.iter_mut().for_each(|field| field.quoted = true);
+        }
+
         let id = id.ok_or_else(|| missing_attr("id"))?;
-        let id = fields
-                .iter()
-            .find(|field| field.field == id)
-                .ok_or_else(|| {
-                    Error::new(
-                        Span::call_site(),
-                        "id does not refer to a field of the struct",
-                    )
-                })?
-            .clone();
+        let id = id
+            .fields
+            .iter()
+            .map(|id| {
+                fields
+                    .iter()
+                    .find(|field| field.field == *id)
+                    .cloned()
+                    .ok_or_else(|| {
+                        Error::new(
+                            id.span(),
+                            "id does not refer to a field of the struct",
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
 
+        if id.iter().filter(|field| field.default).count() > 1 {
+            return Err(Error::new(
+                Span::call_site(),
+                "only one id column can be generated by the database",
+            ));
+        }
+
+        if id.len() > 1 && !(has_many.is_empty() && has_one.is_empty()) {
+            return Err(Error::new(
+                Span::call_site(),
+                "#[cherry(has_many(..))] and #[cherry(has_one(..))] require a single id column",
+            ));
+        }
+
+        if has_many.iter().chain(&has_one).any(|relation| relation.foreign_key.len() > 1) {
+            return Err(Error::new(
+                Span::call_site(),
+                "#[cherry(has_many(..))] and #[cherry(has_one(..))] take a single foreign_key",
+            ));
+        }
+
+        for column in belongs_to.iter().flat_map(|relation| &relation.foreign_key) {
+            if !fields.iter().any(|field| !field.unmapped && field.column_name == *column) {
+                return Err(Error::new(
+                    Span::call_site(),
+                    format!("foreign_key \"{}\" does not refer to a column of the struct", column),
+                ));
+            }
+        }
+
+        let soft_delete = match soft_delete {
+            Some(column) => Some(
+                fields
+                    .iter()
+                    .find(|field| !field.unmapped && field.column_name == column)
+                    .cloned()
+                    .ok_or_else(|| {
+                        Error::new(
+                            Span::call_site(),
+                            format!("soft_delete \"{}\" does not refer to a column of the struct", column),
+                        )
+                    })?,
+            ),
+            None => None,
+        };
+
+        for kind in [TimestampKind::Created, TimestampKind::Updated] {
+            if fields.iter().filter(|field| field.is_timestamp(kind)).count() > 1 {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "only one field can be #[cherry(created_at)] or #[cherry(updated_at)]",
+                ));
+            }
+        }
+
+        if let Some(field) = fields.iter().find(|field| {
+            field.timestamp.is_some()
+                && (field.default || field.unmapped || id.iter().any(|id| id.field == field.field))
+        }) {
+            return Err(Error::new(
+                field.field.span(),
+                "timestamps can not be the id, #[cherry(default)] or #[cherry(unmapped)]",
+            ));
+        }
+
+        if fields.iter().filter(|field| field.version).count() > 1 {
+            return Err(Error::new(
+                Span::call_site(),
+                "only one field can be #[cherry(version)]",
+            ));
+        }
+
+        if let Some(field) = fields.iter().find(|field| {
+            field.version
+                && (field.default
+                    || field.unmapped
+                    || field.timestamp.is_some()
+                    || id.iter().any(|id| id.field == field.field))
+        }) {
+            return Err(Error::new(
+                field.field.span(),
+                "#[cherry(version)] can not be the id, a timestamp, #[cherry(default)] or #[cherry(unmapped)]",
+            ));
+        }
 
         if insertable.is_none() && fields.iter().any(|field| field.default) {
             return Err(Error::new(
@@ -157,6 +326,13 @@ impl<B: Backend> TryFrom<&syn::DeriveInput> for Table<B> {
             id,
             insertable,
             fields,
+            has_many,
+            has_one,
+            belongs_to,
+            soft_delete,
+            hooks: hooks.unwrap_or(false),
+            always_quote,
+            schema,
         })
     }
 }
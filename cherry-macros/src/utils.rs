@@ -23,3 +23,33 @@ pub fn missing_attr(attr: &str) -> Error {
         format!(r#"missing #[cherry({})] attribute"#, attr),
     )
 }
+
+/// converts an UpperCamelCase identifier to snake_case
+pub fn snake_case(ident: &str) -> String {
+    let mut out = String::with_capacity(ident.len() + 4);
+    for (i, c) in ident.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// english plural of a snake_case name, e.g. `category` to `categories`.
+/// Irregular plurals have to be given with `name = ..`.
+pub fn plural(name: &str) -> String {
+    let consonant_y = name.ends_with('y')
+        && !name[..name.len() - 1].ends_with(['a', 'e', 'i', 'o', 'u']);
+    if consonant_y {
+        format!("{}ies", &name[..name.len() - 1])
+    } else if ["s", "x", "z", "ch", "sh"].iter().any(|end| name.ends_with(end)) {
+        format!("{}es", name)
+    } else {
+        format!("{}s", name)
+    }
+}
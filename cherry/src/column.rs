@@ -0,0 +1,35 @@
+use std::fmt::{self, Display, Formatter};
+use std::marker::PhantomData;
+
+/// A column of the table `T`, generated for each of its fields by the derive, e.g. `User::NAME`.
+///
+/// It can be used wherever the query builders take a column name.
+pub struct Column<T> {
+    name: &'static str,
+    _keep: PhantomData<fn() -> T>,
+}
+
+impl<T> Column<T> {
+    pub const fn new(name: &'static str) -> Self {
+        Self { name, _keep: PhantomData }
+    }
+
+    /// Returns the name of this column.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T> Clone for Column<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Column<T> {}
+
+impl<T> Display for Column<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
@@ -1,28 +1,42 @@
 use std::any::Any;
 
 use async_trait::async_trait;
+use futures::Stream;
+use futures::stream::StreamExt;
 
-use crate::{Schema, connection};
+use sqlx::{Encode, Type};
+
+use crate::{Schema, Table, connection};
+use crate::query::copy::CopyIn;
 use crate::query::delete::Delete;
 use crate::query::insert::Insert;
 use crate::query::insert_update::InsertUpdate;
+use crate::query::raw::Raw;
 use crate::query::select::Select;
 use crate::query::update::Update;
-use crate::types::{Result, Transaction};
+use crate::tenant::TenantScope;
+use crate::types::{Database, Result, Transaction};
 
 #[async_trait]
 pub trait DataSource {
 
     fn insert<'a, T>(&'static self, v: &'a T) -> Insert<'a> where T: Schema + 'static {
-        Insert::insert(self.type_id(),  v)
+        Insert::insert(self.type_id(), v, None)
+    }
+
+    fn insert_bulk<'a, T>(&'static self, v: &'a [T]) -> Insert<'a> where T: Schema + Sync + 'static {
+        Insert::insert_bulk(self.type_id(), v, None)
     }
 
-    fn insert_bulk<'a, T>(&'static self, v: &'a [T]) -> Insert<'a> where T: Schema + 'static {
-        Insert::insert_bulk(self.type_id(), v)
+    /// Load a stream of rows, with `COPY` on postgres and chunked inserts elsewhere, see [CopyIn].
+    fn copy_in<'a, T, S>(&'static self, rows: S) -> CopyIn<'a, T>
+        where T: Schema + Sync + 'static, S: Stream<Item = T> + Send + 'a
+    {
+        CopyIn::new(self.type_id(), rows.boxed())
     }
 
     fn insert_ignore<'a, T>(&'static self, v: &'a [T]) -> Insert<'a> where T: Schema + 'static {
-        Insert::insert_ignore(self.type_id(), v)
+        Insert::insert_ignore(self.type_id(), v, None)
     }
 
     fn insert_replace<'a, T>(&'static self, v: &'a [T]) -> Insert<'a> where T: Schema + 'static {
@@ -31,21 +45,53 @@ pub trait DataSource {
 
     fn insert_update<'a, T>(&'static self, v: &'a [T]) -> InsertUpdate<'a>
         where T: Schema + 'static {
-        InsertUpdate::insert_update(self.type_id(), v)
+        InsertUpdate::insert_update(self.type_id(), v, None)
     }
 
     fn delete<'a, T>(&'static self) -> Delete<'a> where T: Schema + 'static {
         Delete::new::<T>(self.type_id())
     }
 
+    fn force_delete<'a, T>(&'static self) -> Delete<'a> where T: Schema + 'static {
+        Delete::force::<T>(self.type_id())
+    }
+
+    /// Delete the rows of the given ids, soft deleting them if the table has a soft delete column.
+    fn delete_by_ids<'a, T>(&'static self, ids: &[T::Id]) -> Delete<'a> where T: Table {
+        Delete::by_ids::<T>(self.type_id(), ids)
+    }
+
+    fn restore<'a, T>(&'static self) -> Update<'a> where T: Schema + 'static {
+        Update::restore::<T>(self.type_id())
+    }
+
     fn update<'a, T>(&'static self) -> Update<'a> where T: Schema + 'static {
         Update::new::<T>(self.type_id())
     }
 
+    /// Update each of the rows by its id, in a single statement.
+    fn update_bulk<'a, T>(&'static self, v: &'a [T]) -> Update<'a> where T: Table {
+        Update::update_bulk(self.type_id(), v)
+    }
+
     fn select<'a, T>(&'static self) -> Select<'a, T> where T: Schema + 'static {
         Select::new(self.type_id())
     }
 
+    /// A query written in sql, with named parameters like `:id` bound by [bind](Raw::bind).
+    fn raw<'a, T>(&'static self, sql: &str) -> Raw<'a, T> {
+        Raw::new(self.type_id(), sql)
+    }
+
+    /// Scope the queries to a tenant, see [TenantScope].
+    fn for_tenant<V>(&'static self, tenant: V) -> TenantScope<Self>
+        where
+            Self: Sized,
+            V: for<'q> Encode<'q, Database> + Type<Database> + Clone + Send + Sync + 'static
+    {
+        TenantScope::new(self, tenant)
+    }
+
     async fn begin<'a>(&'static self) -> Result<Transaction<'a>>  {
         Ok(connection::get(self.type_id())?.begin().await?)
     }
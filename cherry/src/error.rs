@@ -0,0 +1,56 @@
+use std::fmt;
+
+pub use anyhow::Error;
+
+/// Returned when updating a row whose version changed since it was loaded,
+/// see `#[cherry(version)]`.
+#[derive(Debug)]
+pub struct StaleObject {
+    table: &'static str,
+}
+
+impl StaleObject {
+    pub fn new(table: &'static str) -> Self {
+        Self { table }
+    }
+
+    /// Returns the table of the stale row.
+    pub fn table(&self) -> &'static str {
+        self.table
+    }
+
+    /// Returns true if the error was caused by a stale row.
+    pub fn is(err: &sqlx::Error) -> bool {
+        matches!(err, sqlx::Error::Configuration(err) if err.is::<StaleObject>())
+    }
+}
+
+impl fmt::Display for StaleObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Row of `{}` was modified concurrently.", self.table)
+    }
+}
+
+impl std::error::Error for StaleObject {}
+
+/// Converts an error of the query builders or hooks for the `sqlx::Result` of [Table](crate::Table).
+#[doc(hidden)]
+pub fn into_sqlx(err: Error) -> sqlx::Error {
+    err.downcast::<sqlx::Error>()
+        .unwrap_or_else(|err| sqlx::Error::Configuration(err.into()))
+}
+
+/// Reads a `#[cherry(computed)]` field, which is `None` if the query did not select its column.
+#[doc(hidden)]
+pub fn computed<V>(value: sqlx::Result<Option<V>>) -> Result<Option<V>, Error> {
+    match value {
+        Err(sqlx::Error::ColumnNotFound(_)) => Ok(None),
+        value => Ok(value?),
+    }
+}
+
+impl From<StaleObject> for sqlx::Error {
+    fn from(err: StaleObject) -> Self {
+        sqlx::Error::Configuration(Box::new(err))
+    }
+}
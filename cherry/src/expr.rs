@@ -0,0 +1,175 @@
+//! Computed select expressions, see [select_expr](crate::Select::select_expr).
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::anyhow;
+use sqlx::{Decode, Row as SqlxRow, Type};
+
+use crate::query::ident;
+use crate::types::{Database, Result, Row};
+
+/// An sql expression, selected as an extra column.
+#[derive(Debug, Clone)]
+pub struct Expr(String);
+
+impl Expr {
+    /// Evaluate this function over the rows of `window`.
+    pub fn over(self, window: Window) -> Expr {
+        Expr(format!("{} OVER ({})", self.0, window))
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// An expression written in sql, left as it is.
+pub fn expr<S: ToString>(sql: S) -> Expr {
+    Expr(sql.to_string())
+}
+
+fn call(function: &str, column: &str) -> Expr {
+    Expr(format!("{}({})", function, ident::quote(column, false)))
+}
+
+pub fn row_number() -> Expr {
+    Expr("ROW_NUMBER()".to_string())
+}
+
+pub fn rank() -> Expr {
+    Expr("RANK()".to_string())
+}
+
+pub fn dense_rank() -> Expr {
+    Expr("DENSE_RANK()".to_string())
+}
+
+pub fn count(column: &str) -> Expr {
+    call("COUNT", column)
+}
+
+pub fn sum(column: &str) -> Expr {
+    call("SUM", column)
+}
+
+pub fn avg(column: &str) -> Expr {
+    call("AVG", column)
+}
+
+pub fn min(column: &str) -> Expr {
+    call("MIN", column)
+}
+
+pub fn max(column: &str) -> Expr {
+    call("MAX", column)
+}
+
+/// The value of `column` in the previous row of the window.
+pub fn lag(column: &str) -> Expr {
+    call("LAG", column)
+}
+
+/// The value of `column` in the next row of the window.
+pub fn lead(column: &str) -> Expr {
+    call("LEAD", column)
+}
+
+/// The rows a window function is evaluated over: `PARTITION BY .. ORDER BY ..`.
+#[derive(Debug, Clone, Default)]
+pub struct Window {
+    partition: Vec<String>,
+    order: Vec<(String, bool)>,
+}
+
+impl Window {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn partition_by(mut self, column: &str) -> Self {
+        self.partition.push(ident::quote(column, false).into_owned());
+        self
+    }
+
+    pub fn order_by(mut self, column: &str) -> Self {
+        self.order.push((ident::quote(column, false).into_owned(), false));
+        self
+    }
+
+    pub fn order_by_desc(mut self, column: &str) -> Self {
+        self.order.push((ident::quote(column, false).into_owned(), true));
+        self
+    }
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = vec![];
+        if !self.partition.is_empty() {
+            parts.push(format!("PARTITION BY {}", self.partition.join(", ")));
+        }
+        if !self.order.is_empty() {
+            let order: Vec<String> = self.order.iter()
+                .map(|(column, desc)| format!("{}{}", column, if *desc { " DESC" } else { "" }))
+                .collect();
+            parts.push(format!("ORDER BY {}", order.join(", ")));
+        }
+        f.write_str(&parts.join(" "))
+    }
+}
+
+/// A window partitioned by `column`.
+pub fn partition_by(column: &str) -> Window {
+    Window::new().partition_by(column)
+}
+
+/// A window ordered by `column`, over all rows.
+pub fn order_by(column: &str) -> Window {
+    Window::new().order_by(column)
+}
+
+/// Decodes the extra column `alias` of a row as the type given to `select_expr`.
+pub(crate) type Decoder = fn(&Row, &str) -> Result<Box<dyn Any + Send>>;
+
+pub(crate) fn decode<V>(row: &Row, alias: &str) -> Result<Box<dyn Any + Send>>
+    where V: for<'r> Decode<'r, Database> + Type<Database> + Send + 'static
+{
+    Ok(Box::new(row.try_get::<V, _>(alias)?))
+}
+
+/// The values of the expressions selected with `select_expr`, by alias.
+#[derive(Default)]
+pub struct Extras {
+    values: HashMap<String, Box<dyn Any + Send>>,
+}
+
+impl Extras {
+    pub(crate) fn from_row(row: &Row, decoders: &[(String, Decoder)]) -> Result<Self> {
+        let values = decoders.iter()
+            .map(|(alias, decode)| Ok((alias.clone(), decode(row, alias)?)))
+            .collect::<Result<_>>()?;
+        Ok(Self { values })
+    }
+
+    /// The value of the expression `alias`, of the type given to `select_expr`.
+    pub fn get<V: 'static>(&self, alias: &str) -> Result<&V> {
+        self.values.get(alias)
+            .and_then(|value| value.downcast_ref())
+            .ok_or_else(|| anyhow!("No extra column `{}` of type `{}`.", alias, std::any::type_name::<V>()))
+    }
+
+    /// Takes the value of the expression `alias` out, like [get](Self::get).
+    pub fn take<V: 'static>(&mut self, alias: &str) -> Result<V> {
+        match self.values.remove(alias).map(|value| value.downcast::<V>()) {
+            Some(Ok(value)) => Ok(*value),
+            Some(Err(value)) => {
+                self.values.insert(alias.to_string(), value);
+                Err(anyhow!("Extra column `{}` is not of type `{}`.", alias, std::any::type_name::<V>()))
+            }
+            None => Err(anyhow!("No extra column `{}`.", alias)),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+
+use crate::error::Error;
+use crate::types::Transaction;
+
+/// Callbacks run around writes of a model, enabled with `#[cherry(hooks)]`.
+///
+/// Every method does nothing by default. `tx` is the transaction the write runs in: it is given
+/// to the hooks of the `_tx` writes, like [update_tx](crate::Table::update_tx), and of the
+/// inserts run in a transaction, the `_with` writes only get an executor and pass `None`.
+/// Returning an error from a `before_*` hook aborts the write.
+///
+/// The hooks get the row by reference, since the insert builders borrow their rows.
+/// To change a row before it is written, e.g. trimming its fields, use [normalize](Hooks::normalize).
+#[async_trait]
+pub trait Hooks: Send + Sync {
+    /// Changes this row before it is written, ahead of `before_update`.
+    /// Run by [update](crate::Table::update) and [save](crate::Tracked::save) and their variants.
+    /// The insert builders borrow their rows, call it on them before inserting.
+    fn normalize(&mut self) {}
+
+    async fn before_insert(&self, _tx: Option<&mut Transaction<'_>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn after_insert(&self, _tx: Option<&mut Transaction<'_>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn before_update(&self, _tx: Option<&mut Transaction<'_>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn after_update(&self, _tx: Option<&mut Transaction<'_>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn before_delete(&self, _tx: Option<&mut Transaction<'_>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn after_delete(&self, _tx: Option<&mut Transaction<'_>>) -> Result<(), Error> {
+        Ok(())
+    }
+}
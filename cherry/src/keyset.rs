@@ -0,0 +1,221 @@
+//! Keyset pagination, see [paginate_after](crate::Select::paginate_after).
+use anyhow::anyhow;
+use sqlx::{Arguments as SqlxArguments, Encode, Type};
+
+use crate::types::{Arguments, Database, Result};
+
+/// A page of rows, with the cursor of the next page if there is one.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Opaque and url safe, to be handed back to `paginate_after`.
+    pub next_cursor: Option<String>,
+}
+
+/// A value of a key column, written to cursors as text.
+pub trait KeyValue: Sized {
+    fn to_text(&self) -> String;
+
+    fn from_text(text: &str) -> Result<Self>;
+}
+
+macro_rules! key_value {
+    ($($ty: ty),*) => {
+        $(
+            impl KeyValue for $ty {
+                fn to_text(&self) -> String {
+                    self.to_string()
+                }
+
+                fn from_text(text: &str) -> Result<Self> {
+                    Ok(text.parse()?)
+                }
+            }
+        )*
+    };
+}
+
+key_value!(i8, i16, i32, i64, u8, u16, u32, u64, f32, f64, bool, String);
+
+#[cfg(feature = "uuid")]
+key_value!(sqlx::types::Uuid);
+
+#[cfg(feature = "chrono")]
+mod chrono {
+    use sqlx::types::chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+    use super::KeyValue;
+    use crate::types::Result;
+
+    const FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+    impl KeyValue for NaiveDate {
+        fn to_text(&self) -> String {
+            self.to_string()
+        }
+
+        fn from_text(text: &str) -> Result<Self> {
+            Ok(text.parse()?)
+        }
+    }
+
+    impl KeyValue for NaiveDateTime {
+        fn to_text(&self) -> String {
+            self.format(FORMAT).to_string()
+        }
+
+        fn from_text(text: &str) -> Result<Self> {
+            Ok(NaiveDateTime::parse_from_str(text, FORMAT)?)
+        }
+    }
+
+    impl KeyValue for DateTime<Utc> {
+        fn to_text(&self) -> String {
+            self.naive_utc().to_text()
+        }
+
+        fn from_text(text: &str) -> Result<Self> {
+            Ok(Utc.from_utc_datetime(&NaiveDateTime::from_text(text)?))
+        }
+    }
+}
+
+/// The values of the columns ordering a keyset pagination: a single value or a tuple.
+pub trait Key: Sized + Send + 'static {
+    /// Number of columns.
+    const LEN: usize;
+
+    fn to_texts(&self, texts: &mut Vec<String>);
+
+    fn from_texts(texts: &[String]) -> Result<Self>;
+
+    fn add_arguments(self, arguments: &mut Arguments<'_>);
+}
+
+impl<A> Key for A
+    where A: KeyValue + for<'q> Encode<'q, Database> + Type<Database> + Send + 'static
+{
+    const LEN: usize = 1;
+
+    fn to_texts(&self, texts: &mut Vec<String>) {
+        texts.push(self.to_text());
+    }
+
+    fn from_texts(texts: &[String]) -> Result<Self> {
+        A::from_text(&texts[0])
+    }
+
+    fn add_arguments(self, arguments: &mut Arguments<'_>) {
+        arguments.add(self);
+    }
+}
+
+macro_rules! key_tuple {
+    ($len: literal; $($ty: ident $index: tt),*) => {
+        impl<$($ty),*> Key for ($($ty,)*)
+            where $($ty: KeyValue + for<'q> Encode<'q, Database> + Type<Database> + Send + 'static),*
+        {
+            const LEN: usize = $len;
+
+            fn to_texts(&self, texts: &mut Vec<String>) {
+                $( texts.push(self.$index.to_text()); )*
+            }
+
+            fn from_texts(texts: &[String]) -> Result<Self> {
+                Ok(($( $ty::from_text(&texts[$index])?, )*))
+            }
+
+            fn add_arguments(self, arguments: &mut Arguments<'_>) {
+                $( arguments.add(self.$index); )*
+            }
+        }
+    };
+}
+
+key_tuple!(1; A 0);
+key_tuple!(2; A 0, B 1);
+key_tuple!(3; A 0, B 1, C 2);
+key_tuple!(4; A 0, B 1, C 2, D 3);
+
+/// Writes `key` as a cursor: the length prefixed texts of its values, in base64url.
+pub(crate) fn encode<K: Key>(key: &K) -> String {
+    let mut texts = Vec::with_capacity(K::LEN);
+    key.to_texts(&mut texts);
+    let raw: String = texts.iter().map(|text| format!("{}:{}", text.len(), text)).collect();
+    base64_encode(raw.as_bytes())
+}
+
+/// Reads the key written by [encode].
+pub(crate) fn decode<K: Key>(cursor: &str) -> Result<K> {
+    let invalid = || anyhow!("Invalid cursor `{}`.", cursor);
+    let raw = String::from_utf8(base64_decode(cursor).ok_or_else(invalid)?).map_err(|_| invalid())?;
+    let mut rest = raw.as_str();
+    let mut texts = Vec::with_capacity(K::LEN);
+    while !rest.is_empty() {
+        let (len, tail) = rest.split_once(':').ok_or_else(invalid)?;
+        let len: usize = len.parse().map_err(|_| invalid())?;
+        let text = tail.get(..len).ok_or_else(invalid)?;
+        texts.push(text.to_string());
+        rest = &tail[len..];
+    }
+    if texts.len() != K::LEN {
+        return Err(invalid());
+    }
+    K::from_texts(&texts)
+}
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() * 4).div_ceil(3));
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        (0..=chunk.len()).for_each(|i| out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char));
+    }
+    out
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.as_bytes().chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut n = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = ALPHABET.iter().position(|a| a == c)? as u32;
+            n |= value << (18 - 6 * i);
+        }
+        (0..chunk.len() - 1).for_each(|i| out.push((n >> (16 - 8 * i)) as u8));
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursors_round_trip() {
+        assert_eq!(decode::<i64>(&encode(&-42i64)).unwrap(), -42);
+        let key = ("a:1, 2:".to_string(), 7i32);
+        assert_eq!(decode::<(String, i32)>(&encode(&key)).unwrap(), key);
+        let key = (String::new(), true, 1.5f64);
+        assert_eq!(decode::<(String, bool, f64)>(&encode(&key)).unwrap(), key);
+    }
+
+    #[test]
+    fn cursors_are_url_safe() {
+        let cursor = encode(&("ÿÿÿ?>>".to_string(), i64::MIN));
+        assert!(cursor.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'), "{}", cursor);
+    }
+
+    #[test]
+    fn invalid_cursors_are_rejected() {
+        assert!(decode::<i64>("not a cursor").is_err());
+        assert!(decode::<i64>(&encode(&"abc".to_string())).is_err());
+        assert!(decode::<i64>(&encode(&(1i64, 2i64))).is_err());
+        assert!(decode::<(i64, i64)>(&encode(&1i64)).is_err());
+        assert!(decode::<i64>(&base64_encode(b"9:1")).is_err());
+    }
+}
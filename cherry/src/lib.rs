@@ -1,26 +1,45 @@
 // #![allow(unused_imports, deprecated, unused_must_use, unused_mut, unused_variables, dead_code, unreachable_code)]
 
+pub(crate) mod column;
 pub(crate) mod schema;
 pub(crate) mod datasource;
+pub(crate) mod hooks;
+pub(crate) mod page;
 pub(crate) mod query;
 pub(crate) mod table;
+pub(crate) mod timestamp;
+pub(crate) mod tracked;
+pub(crate) mod tenant;
+#[cfg(test)]
+pub(crate) mod testing;
 
 
 pub use cherry_macros::*;
+pub use async_trait::async_trait;
 
 pub use {
+    column::Column,
     schema::Schema,
+    timestamp::Timestamp,
+    tracked::Tracked,
+    tenant::TenantScope,
     datasource::DataSource,
+    hooks::Hooks,
     table::{Table,Insert,Patch,Db,ConnectOptions},
     query::select::Select,
+    query::preload::Preload,
+    query::raw::Raw,
+    query::copy::{CopyIn, CopyRow},
 };
 
+pub use page::Page;
+
 pub mod types;
 pub mod connection;
 
-pub mod error {
-    pub use anyhow::Error;
-}
+pub mod error;
+pub mod expr;
+pub mod keyset;
 
 #[doc(hidden)]
 pub mod exports {
@@ -29,6 +48,11 @@ pub mod exports {
 
 pub mod sqlx {
     pub use sqlx::{Database, Decode, Encode, Arguments, Row, types::Type};
+    pub use sqlx::database::{HasArguments, HasValueRef};
+    pub use sqlx::encode::IsNull;
+    pub use sqlx::error::BoxDynError;
+    #[cfg(feature = "postgres")]
+    pub use sqlx::postgres::PgTypeInfo;
 
     #[cfg(feature = "json")]
     pub use sqlx::types::Json;
@@ -45,6 +69,23 @@ pub mod sqlx {
     // pub use sqlx::mssql::{Mssql, MssqlArguments, MssqlQueryResult, MssqlRow};
 }
 
+/// The type of a `#[derive(CherryEnum)]` with `#[cherry(type_name = ..)]`, a native postgres enum.
+#[doc(hidden)]
+#[cfg(feature = "postgres")]
+#[macro_export]
+macro_rules! __native_enum_type_info {
+    ($type_name:expr) => { $crate::sqlx::PgTypeInfo::with_name($type_name) };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "postgres"))]
+#[macro_export]
+macro_rules! __native_enum_type_info {
+    ($type_name:expr) => {
+        compile_error!("#[cherry(type_name = ..)] maps to a native enum, which only postgres supports")
+    };
+}
+
 #[cfg(not(any(feature = "mysql", feature = "postgres", feature = "sqlite", feature = "mssql")))]
 compile_error!("one of the features ['mysql', 'postgres', 'sqlite', 'mssql'] must be enabled");
 
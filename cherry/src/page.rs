@@ -0,0 +1,24 @@
+/// A page of rows, numbered from 1, see [paginate](crate::Select::paginate).
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Number of rows of all pages, 0 for a page past the last one.
+    pub total: u64,
+    pub page: u64,
+    pub per_page: u64,
+    pub total_pages: u64,
+}
+
+impl<T> Page<T> {
+    pub(crate) fn new(items: Vec<T>, total: u64, page: u64, per_page: u64) -> Self {
+        Self { items, total, page, per_page, total_pages: total.div_ceil(per_page) }
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.page < self.total_pages
+    }
+
+    pub fn has_previous(&self) -> bool {
+        self.page > 1
+    }
+}
@@ -0,0 +1,209 @@
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+use anyhow::anyhow;
+use futures::stream::{BoxStream, StreamExt};
+use sqlx::{Encode, Type};
+
+use crate::{Schema, connection};
+use crate::types::{Database, Result};
+
+/// Rows sent to the database at once.
+const CHUNK_SIZE: usize = 1000;
+
+/// Loads a stream of rows, created by [copy_in](crate::DataSource::copy_in).
+///
+/// On postgres the rows are sent with a binary `COPY FROM STDIN`, so the types of the fields have
+/// to match the types of the columns exactly. On MySQL and SQLite they are inserted with
+/// `insert_bulk`, in a single transaction. A MySQL path with `LOAD DATA LOCAL INFILE` is not
+/// implemented.
+///
+/// Hooks are not run, and columns filled by a database expression are left to their default.
+pub struct CopyIn<'a, T> {
+    _keep: PhantomData<T>,
+    datasource: TypeId,
+    rows: BoxStream<'a, T>,
+    chunk_size: usize,
+}
+
+impl<'a, T> CopyIn<'a, T> where T: Schema + Sync {
+
+    pub(crate) fn new(datasource: TypeId, rows: BoxStream<'a, T>) -> Self {
+        Self { _keep: PhantomData, datasource, rows, chunk_size: CHUNK_SIZE }
+    }
+
+    /// Send `size` rows at once, instead of 1000.
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = size.max(1);
+        self
+    }
+
+    /// Loads the rows, returning how many were loaded.
+    #[cfg(feature = "postgres")]
+    pub async fn execute(self) -> Result<u64> {
+        use crate::query::ident;
+
+        let columns: Vec<String> = T::columns().into_iter()
+            .zip(T::holders())
+            .filter(|(_, holder)| *holder == "?")
+            .map(|(column, _)| ident::quote(column, T::always_quote()).into_owned())
+            .collect();
+        let table = ident::quote(T::table(), T::always_quote());
+        let table = match T::schema() {
+            Some(schema) => format!("{}.{}", ident::quote(schema, T::always_quote()), table),
+            None => table.into_owned(),
+        };
+        let statement = format!("COPY {} ({}) FROM STDIN WITH (FORMAT binary)", table, columns.join(", "));
+
+        let mut copy = connection::get(self.datasource)?.copy_in_raw(&statement).await?;
+        let mut row = CopyRow::new();
+        let mut chunks = self.rows.chunks(self.chunk_size);
+        while let Some(chunk) = chunks.next().await {
+            for v in &chunk {
+                row.start();
+                let added = v.copy_arguments(&mut row).and_then(|()| row.end(columns.len(), T::table()));
+                if let Err(err) = added {
+                    copy.abort(err.to_string()).await?;
+                    return Err(err);
+                }
+            }
+            copy.send(row.take()).await?;
+        }
+        copy.send(row.finish()).await?;
+        Ok(copy.finish().await?)
+    }
+
+    /// Loads the rows, returning how many were loaded.
+    #[cfg(not(feature = "postgres"))]
+    pub async fn execute(self) -> Result<u64> {
+        use crate::query::insert::Insert;
+
+        let mut tx = connection::get(self.datasource)?.begin().await?;
+        let mut chunks = self.rows.chunks(self.chunk_size);
+        let mut count = 0;
+        while let Some(chunk) = chunks.next().await {
+            let mut insert = Insert::insert_bulk(self.datasource, &chunk, None);
+            insert.hooks.clear();
+            count += insert.execute_with(&mut tx).await?.rows_affected();
+        }
+        tx.commit().await?;
+        Ok(count)
+    }
+
+}
+
+/// The data of a binary `COPY`, to which [Schema::copy_arguments] adds the values of a row.
+pub struct CopyRow {
+    data: Vec<u8>,
+    /// Offset of the field count of the current row, and its number of fields.
+    row: usize,
+    fields: usize,
+}
+
+impl CopyRow {
+
+    #[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+    fn new() -> Self {
+        // Signature, flags and header extension length.
+        let mut data = b"PGCOPY\n\xff\r\n\0".to_vec();
+        data.extend_from_slice(&[0; 8]);
+        Self { data, row: 0, fields: 0 }
+    }
+
+    /// Adds the value of the next column, encoded as for a query on postgres.
+    pub fn add<'q, V>(&mut self, value: V) where V: Encode<'q, Database> + Type<Database> {
+        #[cfg(feature = "postgres")]
+        {
+            let mut buffer = sqlx::postgres::PgArgumentBuffer::default();
+            let len = match value.encode(&mut buffer) {
+                sqlx::encode::IsNull::No => buffer.len() as i32,
+                sqlx::encode::IsNull::Yes => -1,
+            };
+            self.data.extend_from_slice(&len.to_be_bytes());
+            self.data.extend_from_slice(&buffer);
+        }
+        #[cfg(not(feature = "postgres"))]
+        drop(value);
+        self.fields += 1;
+    }
+
+    #[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+    fn start(&mut self) {
+        self.row = self.data.len();
+        self.fields = 0;
+        self.data.extend_from_slice(&[0; 2]);
+    }
+
+    /// Writes the field count of the row, which has to have a value for each of the `columns`.
+    #[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+    fn end(&mut self, columns: usize, table: &str) -> Result<()> {
+        if self.fields != columns {
+            return Err(anyhow!("Expected {} values for a row of `{}`, got {}.", columns, table, self.fields));
+        }
+        self.data[self.row..self.row + 2].copy_from_slice(&(columns as i16).to_be_bytes());
+        Ok(())
+    }
+
+    /// The data of the rows added since the last call.
+    #[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+    fn take(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.data)
+    }
+
+    /// The rest of the data, with its trailer.
+    #[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+    fn finish(&mut self) -> Vec<u8> {
+        self.data.extend_from_slice(&(-1i16).to_be_bytes());
+        self.take()
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn rows_are_encoded_in_the_binary_format() {
+        let mut row = CopyRow::new();
+        row.start();
+        row.add(1i32);
+        row.add(None::<i32>);
+        row.end(2, "t").unwrap();
+        let data = row.finish();
+
+        let mut expected = b"PGCOPY\n\xff\r\n\0".to_vec();
+        expected.extend_from_slice(&[0; 8]);
+        expected.extend_from_slice(&2i16.to_be_bytes());
+        expected.extend_from_slice(&4i32.to_be_bytes());
+        expected.extend_from_slice(&1i32.to_be_bytes());
+        expected.extend_from_slice(&(-1i32).to_be_bytes());
+        expected.extend_from_slice(&(-1i16).to_be_bytes());
+        assert_eq!(data, expected);
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn taken_data_is_not_sent_again() {
+        let mut row = CopyRow::new();
+        row.start();
+        row.add(1i32);
+        row.end(1, "t").unwrap();
+        assert_eq!(row.take().len(), 19 + 2 + 8);
+        row.start();
+        row.add(2i32);
+        row.end(1, "t").unwrap();
+        let expected = [&1i16.to_be_bytes()[..], &4i32.to_be_bytes(), &2i32.to_be_bytes(), &(-1i16).to_be_bytes()];
+        assert_eq!(row.finish(), expected.concat());
+    }
+
+    #[test]
+    fn rows_need_a_value_for_each_column() {
+        let mut row = CopyRow::new();
+        row.start();
+        row.add(1i32);
+        let err = row.end(2, "user").unwrap_err();
+        assert_eq!(err.to_string(), "Expected 2 values for a row of `user`, got 1.");
+    }
+}
@@ -4,8 +4,9 @@ use sql_builder::SqlBuilder;
 use sqlx::encode::Encode;
 use sqlx::types::Type;
 
-use crate::{Schema, connection, gen_execute, gen_where};
-use crate::query::query_builder::QueryBuilder;
+use crate::{Schema, Table, connection, gen_execute, gen_table, gen_where};
+use crate::query::query_builder::{Deleted, QueryBuilder};
+use crate::query::raw;
 use crate::types::{Database, QueryResult, Result, Transaction};
 
 pub struct Delete<'a> {
@@ -14,16 +15,38 @@ pub struct Delete<'a> {
 
 impl<'a> Delete<'a> {
 
+    /// Soft deletes the rows if the table has a soft delete column, otherwise deletes them.
     pub(crate) fn new<T: Schema>(datasource: TypeId) -> Self {
+        match T::soft_delete_column() {
+            Some(column) => {
+                let mut query = QueryBuilder::new::<T>(datasource, |table| SqlBuilder::update_table(table));
+                query.sql_builder.set(query.ident(column), "CURRENT_TIMESTAMP");
+                query.deleted = Deleted::Excluded;
+                Self { query }
+            }
+            None => Self::force::<T>(datasource),
+        }
+    }
+
+    /// Deletes the rows, even if the table has a soft delete column.
+    pub(crate) fn force<T: Schema>(datasource: TypeId) -> Self {
         Self {
-            query: QueryBuilder::new::<T>(datasource, SqlBuilder::delete_from(T::table()))
+            query: QueryBuilder::new::<T>(datasource, |table| SqlBuilder::delete_from(table))
         }
     }
 
-    fn build_sql(&mut self) -> Result<String> {
-        Ok(self.query.sql_builder.sql()?)
+    /// Deletes the rows of the given ids, like `new`.
+    pub(crate) fn by_ids<T: Table>(datasource: TypeId, ids: &[T::Id]) -> Self {
+        let mut delete = Self::new::<T>(datasource);
+        delete.query.and_where_ids::<T>(ids.to_vec());
+        delete
+    }
+
+    pub(crate) fn build_sql(&mut self) -> Result<String> {
+        Ok(raw::placeholders(&self.query.sql()?))
     }
 
+    gen_table!();
     gen_where!();
     gen_execute!();
 
@@ -3,21 +3,21 @@ macro_rules! gen_execute {
     () => {
         pub async fn execute(mut self) -> Result<QueryResult> {
             let pool = connection::get(self.query.datasource)?;
-            let result = sqlx::query_with(self.build_sql()?.as_str(), self.query.arguments)
+            let result = sqlx::query_with(self.build_sql()?.as_str(), self.query.take_arguments())
                 .execute(pool).await?;
             Ok(QueryResult::from(result))
         }
 
         pub async fn execute_tx(mut self) -> Result<QueryResult> {
             let mut tx = connection::get(self.query.datasource)?.begin().await?;
-            let result = sqlx::query_with(self.build_sql()?.as_str(), self.query.arguments)
+            let result = sqlx::query_with(self.build_sql()?.as_str(), self.query.take_arguments())
                 .execute(&mut tx).await?;
             tx.commit().await?;
             Ok(QueryResult::from(result))
         }
 
         pub async fn execute_with(mut self, tx: &mut Transaction<'a>) -> Result<QueryResult>  {
-            let result = sqlx::query_with(self.build_sql()?.as_str(), self.query.arguments)
+            let result = sqlx::query_with(self.build_sql()?.as_str(), self.query.take_arguments())
                 .execute(tx).await?;
             Ok(QueryResult::from(result))
         }
@@ -184,6 +184,52 @@ macro_rules! gen_where {
             self
         }
 
+        /// Compares the value at a JSON path such as `$.theme` or `$.tags[0]` of a json column.
+        pub fn and_where_json_eq<S, P, V>(mut self, f: S, path: P, v: V) -> Self
+            where
+                S: ToString,
+                P: ToString,
+                V: Encode<'a, Database> + Type<Database> + Send + 'a
+        {
+            self.query.and_where_json_eq(f, path, v);
+            self
+        }
+
+        /// Compares two columns, e.g. `invoice.user_id` of a subquery with `user.id` of the outer query.
+        pub fn and_where_column_eq<S: ToString, C: ToString>(mut self, f: S, other: C) -> Self {
+            self.query.and_where_column_eq(f, other);
+            self
+        }
+
+        /// `f IN (select)`, binding the arguments of `select` in place.
+        pub fn and_where_in_select<S, U>(mut self, f: S, select: $crate::Select<'a, U>) -> Self
+            where
+                S: ToString,
+                U: $crate::Schema
+        {
+            self.query.and_where_in_select(f, select);
+            self
+        }
+
+        pub fn and_where_not_in_select<S, U>(mut self, f: S, select: $crate::Select<'a, U>) -> Self
+            where
+                S: ToString,
+                U: $crate::Schema
+        {
+            self.query.and_where_not_in_select(f, select);
+            self
+        }
+
+        pub fn and_where_exists<U: $crate::Schema>(mut self, select: $crate::Select<'a, U>) -> Self {
+            self.query.and_where_exists(select);
+            self
+        }
+
+        pub fn and_where_not_exists<U: $crate::Schema>(mut self, select: $crate::Select<'a, U>) -> Self {
+            self.query.and_where_not_exists(select);
+            self
+        }
+
         // ***********************************************************************
 
         pub fn or_where_eq<S, V>(mut self, f: S, v: V) -> Self
@@ -296,3 +342,23 @@ macro_rules! gen_where {
         }
     }
 }
+
+#[macro_export]
+macro_rules! gen_table {
+    () => {
+        /// Use the given table instead of the one of the model, e.g. a partition like
+        /// `events_2026_10`. The name is always quoted.
+        #[allow(clippy::wrong_self_convention)]
+        pub fn from_table<S: ToString>(mut self, table: S) -> Self {
+            self.query.from_table(table);
+            self
+        }
+
+        /// Qualify the table with the given schema, overriding `#[cherry(schema = ..)]`.
+        /// The name is always quoted.
+        pub fn in_schema<S: ToString>(mut self, schema: S) -> Self {
+            self.query.in_schema(schema);
+            self
+        }
+    };
+}
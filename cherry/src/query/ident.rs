@@ -0,0 +1,91 @@
+//! Quoting of table and column names.
+
+use std::borrow::Cow;
+
+#[cfg(feature = "mysql")]
+const QUOTE: char = '`';
+#[cfg(not(feature = "mysql"))]
+const QUOTE: char = '"';
+
+#[cfg(feature = "mysql")]
+const RESERVED: &[&str] = include!("../../../cherry-macros/src/backend/reserved/mysql.rs");
+
+#[cfg(feature = "postgres")]
+const RESERVED: &[&str] = include!("../../../cherry-macros/src/backend/reserved/postgres.rs");
+
+#[cfg(feature = "sqlite")]
+const RESERVED: &[&str] = include!("../../../cherry-macros/src/backend/reserved/sqlite.rs");
+
+/// Quotes `name` if it is a reserved word, or with `always`. A name qualified with its table, like
+/// `user.order`, is quoted part by part. Anything other than plain identifiers, like expressions,
+/// `*` or already quoted names, is left as it is.
+pub(crate) fn quote(name: &str, always: bool) -> Cow<'_, str> {
+    if !name.split('.').all(is_plain) || !(always || name.split('.').any(needs_quote)) {
+        return Cow::Borrowed(name);
+    }
+    let parts: Vec<String> = name.split('.').map(|part| format!("{0}{1}{0}", QUOTE, part)).collect();
+    parts.join(".").into()
+}
+
+/// Quotes `name` whatever it contains, for names given at runtime.
+pub(crate) fn quote_always(name: &str) -> String {
+    let escaped = name.replace(QUOTE, &format!("{0}{0}", QUOTE));
+    format!("{0}{1}{0}", QUOTE, escaped)
+}
+
+fn is_plain(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Unquoted identifiers are folded to lower case by postgres.
+#[cfg(feature = "postgres")]
+fn needs_quote(name: &str) -> bool {
+    name.chars().any(|c| c.is_ascii_uppercase()) || RESERVED.contains(&&*name.to_uppercase())
+}
+
+#[cfg(not(feature = "postgres"))]
+fn needs_quote(name: &str) -> bool {
+    RESERVED.contains(&&*name.to_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quoted(name: &str) -> String {
+        format!("{0}{1}{0}", QUOTE, name)
+    }
+
+    #[test]
+    fn reserved_words_are_quoted() {
+        assert_eq!(quote("order", false), quoted("order"));
+        assert_eq!(quote("user.order", false), format!("{}.{}", quoted("user"), quoted("order")));
+        assert_eq!(quote("name", false), "name");
+    }
+
+    #[test]
+    fn always_quotes_plain_names() {
+        assert_eq!(quote("name", true), quoted("name"));
+        assert_eq!(quote("user.name", true), format!("{}.{}", quoted("user"), quoted("name")));
+    }
+
+    #[test]
+    fn other_names_are_left_as_they_are() {
+        for name in ["*", "user.*", "COUNT(*)", "a + b", "1st", "", &quoted("order")] {
+            assert_eq!(quote(name, true), name);
+        }
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn upper_case_is_quoted_on_postgres() {
+        assert_eq!(quote("userId", false), quoted("userId"));
+    }
+
+    #[test]
+    fn quote_always_escapes_the_quote() {
+        assert_eq!(quote_always("a b"), quoted("a b"));
+        assert_eq!(quote_always(&format!("a{}b", QUOTE)), quoted(&format!("a{0}{0}b", QUOTE)));
+    }
+}
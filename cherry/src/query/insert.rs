@@ -1,72 +1,259 @@
 use std::any::TypeId;
+use std::ops::Range;
 
+use anyhow::anyhow;
+use futures::stream::{self, StreamExt, TryStreamExt};
 use sql_builder::SqlBuilder;
 
-use crate::{Schema, connection, gen_execute};
-use crate::query::query_builder::QueryBuilder;
-use crate::types::{QueryResult, Result, Transaction};
+use crate::{Hooks, Schema, connection, gen_table};
+use crate::query::query_builder::{QueryBuilder, Tenant};
+use crate::query::raw;
+use crate::types::{Arguments, QueryResult, Result, Transaction};
+
+/// Most placeholders the database accepts in a statement.
+#[cfg(any(feature = "mysql", feature = "postgres"))]
+pub(crate) const MAX_PARAMETERS: usize = 65535;
+#[cfg(feature = "sqlite")]
+pub(crate) const MAX_PARAMETERS: usize = 999;
+
+/// Adds the arguments of a range of the inserted rows, so that they can be inserted in chunks.
+type Rows<'a> = Box<dyn Fn(Range<usize>, &mut Arguments<'a>) + Send + Sync + 'a>;
 
 pub struct Insert<'a> {
     pub(crate) query: QueryBuilder<'a>,
     pub(crate) columns: Vec<&'static str>,
+    pub(crate) holders: Vec<&'static str>,
     pub(crate) replace: Option<(String, String)>,
     pub(crate) size: usize,
+    pub(crate) hooks: Vec<&'a dyn Hooks>,
+    /// The rows of a bulk insert, whose arguments are added chunk by chunk rather than to `query`.
+    rows: Option<Rows<'a>>,
+    chunk_size: Option<usize>,
+    parallel: usize,
 }
 
 impl<'a> Insert<'a> {
 
     pub(crate) fn new<T>(datasource: TypeId) -> Self where T: Schema {
         Self {
-            query: QueryBuilder::new::<T>(datasource, SqlBuilder::insert_into(T::table())),
+            query: QueryBuilder::new::<T>(datasource, |table| SqlBuilder::insert_into(table)),
             columns: T::columns(),
+            holders: T::holders().into_iter().map(raw::param).collect(),
             replace: None,
             size: 0,
+            hooks: vec![],
+            rows: None,
+            chunk_size: None,
+            parallel: 1,
         }
     }
 
-    pub(crate) fn insert<T>(datasource: TypeId, v: &'a T) -> Self where T: Schema {
+    /// Adds the `rows`, filling their tenant column if they are inserted for a tenant.
+    fn rows<T>(datasource: TypeId, v: &'a [T], tenant: Option<Tenant>) -> Self where T: Schema {
         let mut t = Self::new::<T>(datasource);
-        t.size = 1;
-        v.arguments(&mut t.query.arguments);
+        t.size = v.len();
+        t.hooks.extend(v.iter().filter_map(Schema::hooks));
+        match tenant {
+            Some(tenant) => t.query.tenant_rows(&mut t.columns, &mut t.holders, v, tenant),
+            None => v.iter().for_each(|v| v.arguments(&mut t.query.arguments)),
+        }
         t
     }
 
-    pub(crate) fn insert_bulk<T>(datasource: TypeId, v: &'a [T]) -> Self where T: Schema {
+    pub(crate) fn insert<T>(datasource: TypeId, v: &'a T, tenant: Option<Tenant>) -> Self where T: Schema {
+        Self::rows(datasource, std::slice::from_ref(v), tenant)
+    }
+
+    /// Inserts the rows in as many statements as the parameter limit of the database requires.
+    pub(crate) fn insert_bulk<T>(datasource: TypeId, v: &'a [T], tenant: Option<Tenant>) -> Self
+        where T: Schema + Sync
+    {
         let mut t = Self::new::<T>(datasource);
         t.size = v.len();
-        v.iter().for_each(|v| v.arguments(&mut t.query.arguments) );
+        t.hooks.extend(v.iter().filter_map(Schema::hooks));
+        let tenant = tenant.filter(|tenant| t.query.tenant_columns(&mut t.columns, &mut t.holders, tenant));
+        t.rows = Some(match tenant {
+            Some(tenant) => Box::new(move |rows: Range<usize>, arguments: &mut Arguments<'a>| {
+                v[rows].iter().for_each(|row| {
+                    row.tenant_arguments(arguments);
+                    tenant(arguments);
+                })
+            }),
+            None => Box::new(move |rows: Range<usize>, arguments: &mut Arguments<'a>| {
+                v[rows].iter().for_each(|row| row.arguments(arguments))
+            }),
+        });
         t
     }
 
-    pub(crate) fn insert_ignore<T>(datasource: TypeId, v: &'a [T]) -> Self where T: Schema {
-        let mut t = Self::new::<T>(datasource);
-        t.size = v.len();
+    pub(crate) fn insert_ignore<T>(datasource: TypeId, v: &'a [T], tenant: Option<Tenant>) -> Self where T: Schema {
+        let mut t = Self::rows(datasource, v, tenant);
         t.replace = Some(("INSERT".into(), "INSERT IGNORE".into()));
-        v.iter().for_each(|v| v.arguments(&mut t.query.arguments) );
         t
     }
 
     pub(crate) fn insert_replace<T>(datasource: TypeId, v: &'a [T]) -> Self where T: Schema {
-        let mut t = Self::new::<T>(datasource);
-        t.size = v.len();
+        let mut t = Self::rows(datasource, v, None);
         t.replace = Some(("INSERT INTO".into(), "REPLACE INTO".into()));
-        v.iter().for_each(|v| v.arguments(&mut t.query.arguments) );
         t
     }
 
-    fn build_sql(&mut self) -> Result<String> {
-        let holders = vec!["?"; self.columns.len()];
-        self.query.sql_builder.fields(self.columns.as_slice());
-        (0..self.size).for_each(|_| {
-            self.query.sql_builder.values(holders.as_slice());
+    /// Insert at most `size` rows per statement of a bulk insert, instead of as many as the
+    /// parameter limit of the database allows.
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = Some(size);
+        self
+    }
+
+    /// Run up to `n` statements of a bulk insert at once with `execute`, each on a connection
+    /// of its own. The rows are then not inserted in a single transaction.
+    pub fn parallel(mut self, n: usize) -> Self {
+        self.parallel = n.max(1);
+        self
+    }
+
+    fn build_sql(&mut self, rows: usize) -> Result<String> {
+        // Left without values, for the next chunk.
+        let blank = self.query.sql_builder.clone();
+        let columns: Vec<String> = self.columns.iter().map(|f| self.query.ident(f)).collect();
+        self.query.sql_builder.fields(&columns);
+        (0..rows).for_each(|_| {
+            self.query.sql_builder.values(self.holders.as_slice());
         });
-        let mut sql = self.query.sql_builder.sql()?;
+        let sql = self.query.sql();
+        self.query.sql_builder = blank;
+        let mut sql = sql?;
         if let Some((src, target)) = &self.replace {
             sql = sql.replacen(src.as_str(), target.as_str(), 1);
         }
-        Ok(sql)
+        Ok(raw::placeholders(&sql))
+    }
+
+    /// Rows per statement of a bulk insert.
+    fn chunk_rows(&self) -> usize {
+        chunk_rows(&self.holders, self.chunk_size)
     }
 
-    gen_execute!();
+    /// The sql and the arguments of each statement of the insert.
+    fn statements(&mut self) -> Result<Vec<(String, Arguments<'a>)>> {
+        let rows = match self.rows.take() {
+            Some(rows) => rows,
+            None => return Ok(vec![(self.build_sql(self.size)?, self.query.take_arguments())]),
+        };
+        let chunk = self.chunk_rows();
+        // An empty insert is still built, to be rejected like the others.
+        (0..self.size.max(1)).step_by(chunk)
+            .map(|start| {
+                let end = self.size.min(start + chunk);
+                let sql = self.build_sql(end - start)?;
+                let mut arguments = Arguments::default();
+                rows(start..end, &mut arguments);
+                Ok((sql, arguments))
+            })
+            .collect()
+    }
 
+    /// Runs the `statements` in `tx`, after the `before_insert` hooks and before the `after_insert` ones.
+    async fn execute_statements(
+        hooks: &[&'a dyn Hooks],
+        statements: Vec<(String, Arguments<'a>)>,
+        tx: &mut Transaction<'_>,
+    ) -> Result<QueryResult> {
+        for hooks in hooks {
+            hooks.before_insert(Some(&mut *tx)).await?;
+        }
+        let mut result: Option<QueryResult> = None;
+        for (sql, arguments) in statements {
+            let next = QueryResult::from(sqlx::query_with(sql.as_str(), arguments).execute(&mut *tx).await?);
+            result = Some(match result {
+                Some(result) => result.merge(next),
+                None => next,
+            });
+        }
+        for hooks in hooks {
+            hooks.after_insert(Some(&mut *tx)).await?;
+        }
+        result.ok_or_else(|| anyhow!("Empty insert. This wasn’t supposed to happen."))
+    }
+
+    gen_table!();
+
+    /// Runs the insert, in a transaction if it takes more than one statement.
+    pub async fn execute(mut self) -> Result<QueryResult> {
+        let pool = connection::get(self.query.datasource)?;
+        let mut statements = self.statements()?;
+        if statements.len() > 1 && self.parallel == 1 {
+            let mut tx = pool.begin().await?;
+            let result = Self::execute_statements(&self.hooks, statements, &mut tx).await?;
+            tx.commit().await?;
+            return Ok(result);
+        }
+        for hooks in &self.hooks {
+            hooks.before_insert(None).await?;
+        }
+        let result = match statements.len() {
+            1 => {
+                let (sql, arguments) = statements.remove(0);
+                QueryResult::from(sqlx::query_with(sql.as_str(), arguments).execute(pool).await?)
+            }
+            _ => {
+                let results: Vec<QueryResult> = stream::iter(statements)
+                    .map(|(sql, arguments)| async move {
+                        let result = sqlx::query_with(sql.as_str(), arguments).execute(pool).await?;
+                        Ok::<_, anyhow::Error>(QueryResult::from(result))
+                    })
+                    .buffered(self.parallel)
+                    .try_collect()
+                    .await?;
+                results.into_iter().reduce(QueryResult::merge)
+                    .ok_or_else(|| anyhow!("Empty insert. This wasn’t supposed to happen."))?
+            }
+        };
+        for hooks in &self.hooks {
+            hooks.after_insert(None).await?;
+        }
+        Ok(result)
+    }
+
+    pub async fn execute_tx(mut self) -> Result<QueryResult> {
+        let mut tx = connection::get(self.query.datasource)?.begin().await?;
+        let statements = self.statements()?;
+        let result = Self::execute_statements(&self.hooks, statements, &mut tx).await?;
+        tx.commit().await?;
+        Ok(result)
+    }
+
+    pub async fn execute_with(mut self, tx: &mut Transaction<'_>) -> Result<QueryResult> {
+        let statements = self.statements()?;
+        Self::execute_statements(&self.hooks, statements, tx).await
+    }
+
+}
+
+/// Rows per statement for the placeholders of a row, `chunk_size` being capped by the parameter limit.
+fn chunk_rows(holders: &[&str], chunk_size: Option<usize>) -> usize {
+    let parameters = holders.iter().filter(|holder| **holder == raw::PARAM).count().max(1);
+    let max = (MAX_PARAMETERS / parameters).max(1);
+    chunk_size.map_or(max, |size| size.clamp(1, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::raw::PARAM;
+
+    #[test]
+    fn chunk_rows_fill_the_parameter_limit() {
+        assert_eq!(chunk_rows(&[PARAM, PARAM, PARAM], None), MAX_PARAMETERS / 3);
+        // Columns filled by an expression take no parameter.
+        assert_eq!(chunk_rows(&[PARAM, "NOW()"], None), MAX_PARAMETERS);
+        assert_eq!(chunk_rows(&[], None), MAX_PARAMETERS);
+    }
+
+    #[test]
+    fn chunk_rows_follow_the_chunk_size() {
+        assert_eq!(chunk_rows(&[PARAM, PARAM], Some(100)), 100);
+        assert_eq!(chunk_rows(&[PARAM, PARAM], Some(0)), 1);
+        assert_eq!(chunk_rows(&[PARAM, PARAM], Some(usize::MAX)), MAX_PARAMETERS / 2);
+    }
 }
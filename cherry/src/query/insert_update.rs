@@ -4,32 +4,44 @@ use anyhow::anyhow;
 use sql_builder::SqlBuilder;
 use sqlx::{Encode, Type};
 
-use crate::{Schema, connection, gen_execute, gen_where};
-use crate::query::query_builder::QueryBuilder;
+use crate::{Schema, connection, gen_execute, gen_table, gen_where};
+use crate::query::query_builder::{QueryBuilder, Tenant};
+use crate::query::raw;
 use crate::types::{Database, QueryResult, Result, Transaction};
 
 pub struct InsertUpdate<'a> {
     pub(crate) query: QueryBuilder<'a>,
     pub(crate) columns: Vec<&'static str>,
+    pub(crate) holders: Vec<&'static str>,
     pub(crate) size: usize,
     pub(crate) fields: Vec<String>,
+    pub(crate) scoped: bool,
 }
 
 impl<'a> InsertUpdate<'a> {
 
     fn new<T>(datasource: TypeId) -> Self where T: Schema {
         Self {
-            query: QueryBuilder::new::<T>(datasource, SqlBuilder::insert_into(T::table())),
+            query: QueryBuilder::new::<T>(datasource, |table| SqlBuilder::insert_into(table)),
             columns: T::columns(),
+            holders: T::holders().into_iter().map(raw::param).collect(),
             size: 0,
-            fields: vec![]
+            fields: T::updated_at_column().into_iter().map(String::from).collect(),
+            scoped: false,
         }
     }
 
-    pub(crate) fn insert_update<T>(datasource: TypeId, v: &'a [T]) -> Self where T: Schema {
+    pub(crate) fn insert_update<T>(datasource: TypeId, v: &'a [T], tenant: Option<Tenant>) -> Self
+        where T: Schema {
         let mut t = Self::new::<T>(datasource);
         t.size = v.len();
-        v.iter().for_each(|v| v.arguments(&mut t.query.arguments) );
+        match tenant {
+            Some(tenant) => {
+                t.scoped = true;
+                t.query.tenant_rows(&mut t.columns, &mut t.holders, v, tenant);
+            }
+            None => v.iter().for_each(|v| v.arguments(&mut t.query.arguments)),
+        }
         t
     }
 
@@ -55,6 +67,7 @@ impl<'a> InsertUpdate<'a> {
         self
     }
 
+    gen_table!();
     gen_where!();
 
     fn build_sql(&mut self) -> Result<String> {
@@ -62,21 +75,29 @@ impl<'a> InsertUpdate<'a> {
             return Err(anyhow!("Empty update fields."));
         }
 
-        let holders = vec!["?"; self.columns.len()];
-        self.query.sql_builder.fields(self.columns.as_slice());
+        let columns: Vec<String> = self.columns.iter().map(|f| self.query.ident(f)).collect();
+        self.query.sql_builder.fields(&columns);
         (0..self.size).for_each(|_| {
-            self.query.sql_builder.values(holders.as_slice());
+            self.query.sql_builder.values(self.holders.as_slice());
         });
 
-        let insert = self.query.sql_builder.sql()?.strip_suffix(";")
+        let insert = self.query.sql()?.strip_suffix(";")
             .ok_or(anyhow!("Empty sql. This wasn’t supposed to happen."))?
             .to_owned();
-        let update = self.fields.iter().map(|x| format!("{0} = new.{0}, ", x))
+        // Rows of other tenants are left as they are.
+        let tenant = self.query.tenant_column.filter(|_| self.scoped);
+        let update = self.fields.iter()
+            .filter(|x| Some(x.as_str()) != tenant)
+            .map(|x| match tenant {
+                Some(tenant) => format!("{0} = IF({1} = new.{1}, new.{0}, {0}), ",
+                    self.query.ident(x), self.query.ident(tenant)),
+                None => format!("{0} = new.{0}, ", self.query.ident(x)),
+            })
             .collect::<String>()
             .strip_suffix(",")
             .ok_or(anyhow!("Empty sql. This wasn’t supposed to happen."))?
             .to_owned();
-        Ok(format!("{} AS new ON DUPLICATE KEY UPDATE {};", insert, update))
+        Ok(raw::placeholders(&format!("{} AS new ON DUPLICATE KEY UPDATE {};", insert, update)))
     }
 
     gen_execute!();
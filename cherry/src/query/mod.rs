@@ -1,7 +1,11 @@
 pub(crate) mod query_builder;
+pub(crate) mod ident;
 pub(crate) mod gen_impl;
 pub(crate) mod insert;
+pub(crate) mod copy;
 pub(crate) mod insert_update;
 pub(crate) mod update;
 pub(crate) mod delete;
 pub(crate) mod select;
+pub(crate) mod preload;
+pub(crate) mod raw;
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use sqlx::{Decode, Row as SqlxRow, Type};
+use sqlx::encode::Encode;
+
+use crate::{Schema, Table, connection};
+use crate::query::insert::MAX_PARAMETERS;
+use crate::query::select::Select;
+use crate::types::{Database, Result};
+
+/// Loads the rows of `U` belonging to each selected row of `T` with a single extra query.
+pub struct Preload<'a, T, U> {
+    _keep: PhantomData<U>,
+    select: Select<'a, T>,
+    foreign_key: String,
+}
+
+impl<'a, T, U> Preload<'a, T, U>
+    where
+        T: Table,
+        U: Schema,
+        T::Id: Eq + Hash + Sync
+            + for<'q> Encode<'q, Database>
+            + for<'r> Decode<'r, Database>
+            + Type<Database>,
+{
+
+    pub(crate) fn new(select: Select<'a, T>, foreign_key: &str) -> Self {
+        Self { _keep: PhantomData, select, foreign_key: foreign_key.to_string() }
+    }
+
+    /// Fetch the parent rows, each paired with its children in query order.
+    pub async fn fetch_all(self) -> Result<Vec<(T, Vec<U>)>> {
+        let datasource = self.select.query.datasource;
+        let tenant = self.select.query.tenant.clone();
+        let parents = self.select.fetch_all().await?;
+        if parents.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let ids: Vec<T::Id> = parents.iter().map(|p| p.id()).collect();
+        let mut grouped: HashMap<T::Id, Vec<U>> = HashMap::with_capacity(parents.len());
+        // One query per chunk of ids within the parameter limit, which leaves one for the tenant.
+        for ids in ids.chunks(MAX_PARAMETERS - 1) {
+            let mut children: Select<U> = Select::new(datasource);
+            if U::tenant_column().is_some() {
+                children.query.tenant = tenant.clone();
+            }
+            children.query.and_where_in(&self.foreign_key, ids);
+
+            let sql = children.query_sql()?;
+            let rows = sqlx::query_with(
+                sql.as_str(),
+                children.take_arguments()
+            ).fetch_all(connection::get(datasource)?).await?;
+
+            for row in rows {
+                let id: T::Id = row.try_get(self.foreign_key.as_str())?;
+                grouped.entry(id).or_default().push(U::from_row(&row)?);
+            }
+        }
+
+        Ok(parents.into_iter().map(|p| {
+            let children = grouped.remove(&p.id()).unwrap_or_default();
+            (p, children)
+        }).collect())
+    }
+
+    /// Fetch the parent rows, handing each one its children, e.g. to fill an unmapped field.
+    pub async fn fetch_all_into<F>(self, mut f: F) -> Result<Vec<T>>
+        where F: FnMut(&mut T, Vec<U>)
+    {
+        Ok(self.fetch_all().await?.into_iter().map(|(mut p, children)| {
+            f(&mut p, children);
+            p
+        }).collect())
+    }
+
+}
@@ -1,24 +1,182 @@
 use std::any::TypeId;
 use std::marker::PhantomData;
+use std::sync::Arc;
 
+use anyhow::anyhow;
 use sql_builder::SqlBuilder;
 use sqlx::{Arguments as SqlxArguments, Type};
 use sqlx::encode::Encode;
 
-use crate::Schema;
-use crate::types::{Arguments, Database};
+use crate::{Schema, Table};
+use crate::query::ident;
+use crate::query::raw::PARAM;
+use crate::query::select::Select;
+use crate::types::{Arguments, Database, Result};
+
+/// Which rows of a soft deleted table a query applies to.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Deleted {
+    Excluded,
+    Included,
+    Only,
+}
+
+/// Adds the tenant of a scope as argument.
+pub(crate) type Tenant = Arc<dyn for<'q> Fn(&mut Arguments<'q>) + Send + Sync>;
+
+/// Adds the argument of a where condition, deferred so that subqueries can hand theirs over.
+pub(crate) type Binding<'a> = Box<dyn FnOnce(&mut Arguments<'a>) + Send + 'a>;
+
+/// Stands in for the table in `sql_builder`, until `sql()` replaces it with the qualified table.
+const TABLE: &str = "\u{0}table\u{0}";
 
 pub(crate) struct QueryBuilder<'a> {
     _keep: PhantomData<&'a ()>,
     pub(crate) datasource: TypeId,
     pub(crate) sql_builder: SqlBuilder,
+    /// Arguments of the values set or inserted, followed by the `bindings` of the where conditions.
     pub(crate) arguments: Arguments<'a>,
+    pub(crate) bindings: Vec<Binding<'a>>,
+    /// Error of a subquery, returned by `sql()`.
+    pub(crate) error: Option<anyhow::Error>,
+    pub(crate) table: &'static str,
+    pub(crate) soft_delete: Option<&'static str>,
+    pub(crate) deleted: Deleted,
+    pub(crate) always_quote: bool,
+    pub(crate) schema: Option<String>,
+    pub(crate) from: String,
+    /// Name of the rows of `from_select`, which qualifies the tenant and soft delete conditions.
+    pub(crate) alias: Option<String>,
+    pub(crate) tenant_column: Option<&'static str>,
+    pub(crate) tenant: Option<Tenant>,
+    /// Common table expressions, prepended by `sql()` as `WITH`.
+    pub(crate) ctes: Vec<String>,
+    pub(crate) recursive: bool,
+    /// Number of `bindings` of the ctes, which precede all others.
+    pub(crate) cte_bindings: usize,
 }
 
 impl<'a> QueryBuilder<'a> {
     
-    pub(crate) fn new<T: Schema>(datasource: TypeId, sql_builder: SqlBuilder) -> Self {
-        Self { _keep: PhantomData, datasource, sql_builder, arguments: Arguments::default() }
+    /// Builds the sql with `sql_builder`, e.g. `SqlBuilder::select_from`, on the table of `T`.
+    pub(crate) fn new<T: Schema>(datasource: TypeId, sql_builder: fn(&str) -> SqlBuilder) -> Self {
+        Self {
+            _keep: PhantomData,
+            datasource,
+            sql_builder: sql_builder(TABLE),
+            arguments: Arguments::default(),
+            bindings: vec![],
+            error: None,
+            table: T::table(),
+            soft_delete: T::soft_delete_column(),
+            deleted: Deleted::Included,
+            always_quote: T::always_quote(),
+            schema: T::schema().map(|schema| ident::quote(schema, T::always_quote()).into_owned()),
+            from: ident::quote(T::table(), T::always_quote()).into_owned(),
+            alias: None,
+            tenant_column: T::tenant_column(),
+            tenant: None,
+            ctes: vec![],
+            recursive: false,
+            cte_bindings: 0,
+        }
+    }
+
+    /// Use the given table instead of the one of the model, e.g. a partition.
+    #[allow(clippy::wrong_self_convention)]
+    pub(crate) fn from_table<S: ToString>(&mut self, table: S) -> &mut Self {
+        self.from = ident::quote_always(&table.to_string());
+        self
+    }
+
+    /// Qualify the table with the given schema.
+    pub(crate) fn in_schema<S: ToString>(&mut self, schema: S) -> &mut Self {
+        self.schema = Some(ident::quote_always(&schema.to_string()));
+        self
+    }
+
+    /// Fills the tenant column of the inserted `rows`, moving it to the end of `columns` and `holders`.
+    pub(crate) fn tenant_rows<T: Schema>(
+        &mut self,
+        columns: &mut Vec<&'static str>,
+        holders: &mut Vec<&'static str>,
+        rows: &'a [T],
+        tenant: Tenant,
+    ) {
+        if self.tenant_columns(columns, holders, &tenant) {
+            rows.iter().for_each(|row| {
+                row.tenant_arguments(&mut self.arguments);
+                tenant(&mut self.arguments);
+            });
+        }
+    }
+
+    /// Moves the tenant column to the end of `columns` and `holders`, to be filled with the tenant.
+    /// Returns false if the table has no tenant column, leaving `sql()` to reject the query.
+    pub(crate) fn tenant_columns(
+        &mut self,
+        columns: &mut Vec<&'static str>,
+        holders: &mut Vec<&'static str>,
+        tenant: &Tenant,
+    ) -> bool {
+        let index = match self.tenant_column.and_then(|c| columns.iter().position(|x| *x == c)) {
+            Some(index) => index,
+            None => {
+                self.tenant = Some(tenant.clone());
+                return false;
+            }
+        };
+        let column = columns.remove(index);
+        columns.push(column);
+        holders.remove(index);
+        holders.push(PARAM);
+        true
+    }
+
+    /// Build the final sql, applying the tenant and soft delete scopes of the table.
+    /// The scopes are added to a copy of `sql_builder`, so the sql can be built again.
+    pub(crate) fn sql(&self) -> Result<String> {
+        if let Some(err) = &self.error {
+            return Err(anyhow!("{:#}", err));
+        }
+        let table = match &self.schema {
+            Some(schema) => format!("{}.{}", schema, self.from),
+            None => self.from.clone(),
+        };
+        // Qualified, so that they do not apply to a joined table with the same columns.
+        let qualified = |column| format!("{}.{}", self.alias.as_ref().unwrap_or(&table), self.ident(column));
+        let mut sql_builder = self.sql_builder.clone();
+        // Added last, so that no `or_where` can join it, its argument follows all bindings.
+        if self.tenant.is_some() {
+            let column = self.tenant_column
+                .ok_or_else(|| anyhow!("Table `{}` has no tenant column.", self.table))?;
+            sql_builder.and_where_eq(qualified(column), PARAM);
+        }
+        match (self.deleted, self.soft_delete) {
+            (Deleted::Excluded, Some(column)) => {
+                sql_builder.and_where_is_null(qualified(column));
+            }
+            (Deleted::Only, Some(column)) => {
+                sql_builder.and_where_is_not_null(qualified(column));
+            }
+            (Deleted::Only, None) => {
+                return Err(anyhow!("Table `{}` has no soft delete column.", self.table));
+            }
+            _ => {}
+        }
+        let sql = sql_builder.sql()?.replacen(TABLE, &table, 1);
+        match self.ctes.is_empty() {
+            true => Ok(sql),
+            false => {
+                let with = if self.recursive { "WITH RECURSIVE" } else { "WITH" };
+                Ok(format!("{} {} {}", with, self.ctes.join(", "), sql))
+            }
+        }
+    }
+
+    /// Quotes the column `f` if necessary.
+    pub(crate) fn ident<S: ToString>(&self, f: S) -> String {
+        ident::quote(&f.to_string(), self.always_quote).into_owned()
     }
 
     pub(crate) fn add_arguments<V>(&mut self, v: V) -> &mut Self
@@ -27,12 +185,116 @@ impl<'a> QueryBuilder<'a> {
         self
     }
 
+    /// Binds `v` to the next placeholder of the where conditions.
+    pub(crate) fn bind<V>(&mut self, v: V) -> &mut Self
+        where V: Encode<'a, Database> + Type<Database> + Send + 'a {
+        self.bindings.push(Box::new(move |arguments| arguments.add(v)));
+        self
+    }
+
+    /// Restricts the query to the rows of the given `ids`, or to none if there are no ids.
+    pub(crate) fn and_where_ids<T: Table>(&mut self, ids: Vec<T::Id>) -> &mut Self {
+        let columns: Vec<String> = T::id_columns().iter().map(|column| self.ident(column)).collect();
+        let condition = match (columns.as_slice(), ids.len()) {
+            (_, 0) => "1 = 0".to_string(),
+            ([column], len) => format!("{} IN ({})", column, vec![PARAM; len].join(", ")),
+            (columns, len) => {
+                let id = columns.iter().map(|column| format!("{} = {}", column, PARAM)).collect::<Vec<_>>().join(" AND ");
+                vec![format!("({})", id); len].join(" OR ")
+            }
+        };
+        self.sql_builder.and_where(condition);
+        self.bindings.push(Box::new(move |arguments| {
+            ids.into_iter().for_each(|id| T::id_arguments(id, arguments))
+        }));
+        self
+    }
+
+    /// The arguments of the query, to be taken after building its sql.
+    pub(crate) fn take_arguments(&mut self) -> Arguments<'a> {
+        let mut arguments = std::mem::take(&mut self.arguments);
+        self.take_bindings().into_iter().for_each(|bind| bind(&mut arguments));
+        arguments
+    }
+
+    /// The bindings of the where conditions, followed by the tenant of the scope added by `sql()`.
+    pub(crate) fn take_bindings(&mut self) -> Vec<Binding<'a>> {
+        let mut bindings = std::mem::take(&mut self.bindings);
+        if let Some(tenant) = self.tenant.clone() {
+            bindings.push(Box::new(move |arguments| tenant(arguments)));
+        }
+        bindings
+    }
+
+    /// The sql of `select` and the bindings of its where conditions, to embed it as a subquery.
+    pub(crate) fn subquery<U: Schema>(&mut self, mut select: Select<'a, U>) -> Option<(String, Vec<Binding<'a>>)> {
+        match select.sql() {
+            Ok(sql) => Some((sql.trim_end_matches(';').to_string(), select.take_bindings())),
+            Err(err) => {
+                self.error.get_or_insert(err);
+                None
+            }
+        }
+    }
+
+    /// Selects from the rows of `select`, named `alias`, instead of the table.
+    #[allow(clippy::wrong_self_convention)]
+    pub(crate) fn from_select<U: Schema, S: ToString>(&mut self, select: Select<'a, U>, alias: S) -> &mut Self {
+        if let Some((sql, bindings)) = self.subquery(select) {
+            let alias = ident::quote_always(&alias.to_string());
+            self.schema = None;
+            self.from = format!("({}) AS {}", sql, alias);
+            self.alias = Some(alias);
+            // The table precedes every where condition, and follows the ctes.
+            self.bindings.splice(self.cte_bindings..self.cte_bindings, bindings);
+        }
+        self
+    }
+
+    /// Adds the cte `name`, its `sql` and `bindings` following those of the previous ctes.
+    fn add_cte<S: ToString>(&mut self, name: S, sql: String, bindings: Vec<Binding<'a>>) {
+        self.ctes.push(format!("{} AS ({})", self.ident(name), sql));
+        let at = self.cte_bindings;
+        self.cte_bindings += bindings.len();
+        self.bindings.splice(at..at, bindings);
+    }
+
+    /// Defines the rows of `select` as the cte `name`.
+    pub(crate) fn with_cte<U: Schema, S: ToString>(&mut self, name: S, select: Select<'a, U>) -> &mut Self {
+        if let Some((sql, bindings)) = self.subquery(select) {
+            self.add_cte(name, sql, bindings);
+        }
+        self
+    }
+
+    /// Defines the recursive cte `name`, the rows of `anchor` and, repeatedly, of `recursive`
+    /// which selects from `name`.
+    pub(crate) fn with_recursive<U, V, S>(&mut self, name: S, anchor: Select<'a, U>, recursive: Select<'a, V>) -> &mut Self
+        where U: Schema, V: Schema, S: ToString
+    {
+        if let (Some((anchor, mut bindings)), Some((recursive, more))) = (self.subquery(anchor), self.subquery(recursive)) {
+            bindings.extend(more);
+            self.recursive = true;
+            self.add_cte(name, format!("{} UNION ALL {}", anchor, recursive), bindings);
+        }
+        self
+    }
+
+    /// Joins `table` on `left = right`, with the join operator already set on `sql_builder`.
+    pub(crate) fn join<S, L, R>(&mut self, table: S, left: L, right: R) -> &mut Self
+        where S: ToString, L: ToString, R: ToString
+    {
+        let (table, left, right) = (self.ident(table), self.ident(left), self.ident(right));
+        self.sql_builder.join(table).on_eq(left, right);
+        self
+    }
+
 }
 
 impl<'a> QueryBuilder<'a>{
 
     pub (crate) fn group_by<S: ToString>(&mut self, f: S) -> &mut Self {
-        self.sql_builder.group_by(f);
+        self.sql_builder.group_by(self.ident(f));
         self
     }
 
@@ -41,7 +303,7 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             T: ToString,
     {
-        self.sql_builder.count_as(f, name);
+        self.sql_builder.count_as(self.ident(f), name);
         self
     }
 
@@ -55,8 +317,8 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.and_where_eq(f, '?');
-        self.arguments.add(v);
+        self.sql_builder.and_where_eq(self.ident(f), PARAM);
+        self.bind(v);
         self
     }
 
@@ -65,8 +327,8 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.and_where_ne(f, '?');
-        self.arguments.add(v);
+        self.sql_builder.and_where_ne(self.ident(f), PARAM);
+        self.bind(v);
         self
     }
 
@@ -75,8 +337,8 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.and_where_ge(f, '?');
-        self.arguments.add(v);
+        self.sql_builder.and_where_ge(self.ident(f), PARAM);
+        self.bind(v);
         self
     }
 
@@ -85,8 +347,8 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.and_where_le(f, '?');
-        self.arguments.add(v);
+        self.sql_builder.and_where_le(self.ident(f), PARAM);
+        self.bind(v);
         self
     }
 
@@ -95,8 +357,8 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.and_where_gt(f, '?');
-        self.arguments.add(v);
+        self.sql_builder.and_where_gt(self.ident(f), PARAM);
+        self.bind(v);
         self
     }
 
@@ -105,8 +367,8 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.and_where_lt(f, '?');
-        self.arguments.add(v);
+        self.sql_builder.and_where_lt(self.ident(f), PARAM);
+        self.bind(v);
         self
     }
 
@@ -115,7 +377,7 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: ToString,
     {
-        self.sql_builder.and_where_like(f, v);
+        self.sql_builder.and_where_like(self.ident(f), v);
         // self.arguments.add(v);
         self
     }
@@ -125,7 +387,7 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: ToString,
     {
-        self.sql_builder.and_where_like_any(f, v);
+        self.sql_builder.and_where_like_any(self.ident(f), v);
         // self.arguments.add(v);
         self
     }
@@ -135,18 +397,18 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: ToString,
     {
-        self.sql_builder.and_where_not_like(f, v);
+        self.sql_builder.and_where_not_like(self.ident(f), v);
         self
     }
 
 
     pub(crate) fn and_where_is_null<S>(&mut self, f: S) -> &mut Self where S: ToString {
-        self.sql_builder.and_where_is_null(f);
+        self.sql_builder.and_where_is_null(self.ident(f));
         self
     }
 
     pub(crate) fn and_where_is_not_null<S>(&mut self, f: S) -> &mut Self where S: ToString {
-        self.sql_builder.and_where_is_not_null(f);
+        self.sql_builder.and_where_is_not_null(self.ident(f));
         self
     }
 
@@ -155,9 +417,9 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.and_where_between(f, '?', '?');
-        self.arguments.add(min);
-        self.arguments.add(max);
+        self.sql_builder.and_where_between(self.ident(f), PARAM, PARAM);
+        self.bind(min);
+        self.bind(max);
         self
     }
 
@@ -179,9 +441,9 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.and_where_not_between(f, '?', '?');
-        self.arguments.add(min);
-        self.arguments.add(max);
+        self.sql_builder.and_where_not_between(self.ident(f), PARAM, PARAM);
+        self.bind(min);
+        self.bind(max);
         self
     }
 
@@ -190,9 +452,9 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + Sync + 'a
     {
-        self.sql_builder.and_where_in(f, &vec!["?"; v.len()]);
+        self.sql_builder.and_where_in(self.ident(f), &vec![PARAM; v.len()]);
         v.iter().for_each(|v| {
-            self.arguments.add(v);
+            self.bind(v);
         });
         self
     }
@@ -202,13 +464,77 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + Sync + 'a
     {
-        self.sql_builder.and_where_not_in(f, &vec!["?"; v.len()]);
+        self.sql_builder.and_where_not_in(self.ident(f), &vec![PARAM; v.len()]);
         v.iter().for_each(|v| {
-            self.arguments.add(v);
+            self.bind(v);
         });
         self
     }
 
+    pub(crate) fn and_where_column_eq<S: ToString, C: ToString>(&mut self, f: S, other: C) -> &mut Self {
+        self.sql_builder.and_where_eq(self.ident(f), self.ident(other));
+        self
+    }
+
+    pub(crate) fn and_where_in_select<S: ToString, U: Schema>(&mut self, f: S, select: Select<'a, U>) -> &mut Self {
+        if let Some((sql, bindings)) = self.subquery(select) {
+            self.sql_builder.and_where_in_query(self.ident(f), sql);
+            self.bindings.extend(bindings);
+        }
+        self
+    }
+
+    pub(crate) fn and_where_not_in_select<S: ToString, U: Schema>(&mut self, f: S, select: Select<'a, U>) -> &mut Self {
+        if let Some((sql, bindings)) = self.subquery(select) {
+            self.sql_builder.and_where_not_in_query(self.ident(f), sql);
+            self.bindings.extend(bindings);
+        }
+        self
+    }
+
+    pub(crate) fn and_where_exists<U: Schema>(&mut self, select: Select<'a, U>) -> &mut Self {
+        if let Some((sql, bindings)) = self.subquery(select) {
+            self.sql_builder.and_where(format!("EXISTS ({})", sql));
+            self.bindings.extend(bindings);
+        }
+        self
+    }
+
+    pub(crate) fn and_where_not_exists<U: Schema>(&mut self, select: Select<'a, U>) -> &mut Self {
+        if let Some((sql, bindings)) = self.subquery(select) {
+            self.sql_builder.and_where(format!("NOT EXISTS ({})", sql));
+            self.bindings.extend(bindings);
+        }
+        self
+    }
+
+    /// `f` is a json column, compared as text on postgres.
+    pub(crate) fn and_where_json_eq<S, P, V>(&mut self, f: S, path: P, v: V) -> &mut Self
+        where
+            S: ToString,
+            P: ToString,
+            V: Encode<'a, Database> + Type<Database> + Send + 'a
+    {
+        let path = path.to_string();
+        #[cfg(feature = "mysql")]
+        {
+            self.sql_builder.and_where(format!("JSON_UNQUOTE(JSON_EXTRACT({0}, {1})) = {1}", self.ident(f), PARAM));
+            self.bind(path);
+        }
+        #[cfg(feature = "postgres")]
+        {
+            self.sql_builder.and_where(format!("{0} #>> {1} = CAST({1} AS TEXT)", self.ident(f), PARAM));
+            self.bind(json_path_elements(&path));
+        }
+        #[cfg(feature = "sqlite")]
+        {
+            self.sql_builder.and_where(format!("json_extract({0}, {1}) = {1}", self.ident(f), PARAM));
+            self.bind(path);
+        }
+        self.bind(v);
+        self
+    }
+
     // ***********************************************************************
 
     pub(crate) fn or_where_eq<S, V>(&mut self, f: S, v: V) -> &mut Self
@@ -216,8 +542,8 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.or_where_eq(f, '?');
-        self.arguments.add(v);
+        self.sql_builder.or_where_eq(self.ident(f), PARAM);
+        self.bind(v);
         self
     }
 
@@ -226,8 +552,8 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.or_where_ne(f, '?');
-        self.arguments.add(v);
+        self.sql_builder.or_where_ne(self.ident(f), PARAM);
+        self.bind(v);
         self
     }
 
@@ -236,8 +562,8 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.or_where_ge(f, '?');
-        self.arguments.add(v);
+        self.sql_builder.or_where_ge(self.ident(f), PARAM);
+        self.bind(v);
         self
     }
 
@@ -246,8 +572,8 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.or_where_le(f, '?');
-        self.arguments.add(v);
+        self.sql_builder.or_where_le(self.ident(f), PARAM);
+        self.bind(v);
         self
     }
 
@@ -256,8 +582,8 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.or_where_gt(f, '?');
-        self.arguments.add(v);
+        self.sql_builder.or_where_gt(self.ident(f), PARAM);
+        self.bind(v);
         self
     }
 
@@ -266,8 +592,8 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.or_where_lt(f, '?');
-        self.arguments.add(v);
+        self.sql_builder.or_where_lt(self.ident(f), PARAM);
+        self.bind(v);
         self
     }
 
@@ -276,17 +602,17 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: ToString,
     {
-        self.sql_builder.or_where_like(f, v);
+        self.sql_builder.or_where_like(self.ident(f), v);
         self
     }
 
     pub(crate) fn or_where_is_null<S>(&mut self, f: S) -> &mut Self where S: ToString {
-        self.sql_builder.or_where_is_null(f);
+        self.sql_builder.or_where_is_null(self.ident(f));
         self
     }
 
     pub(crate) fn or_where_is_not_null<S>(&mut self, f: S) -> &mut Self where S: ToString {
-        self.sql_builder.or_where_is_not_null(f);
+        self.sql_builder.or_where_is_not_null(self.ident(f));
         self
     }
 
@@ -295,9 +621,9 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.or_where_between(f, '?', '?');
-        self.arguments.add(min);
-        self.arguments.add(max);
+        self.sql_builder.or_where_between(self.ident(f), PARAM, PARAM);
+        self.bind(min);
+        self.bind(max);
         self
     }
 
@@ -306,9 +632,9 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + 'a
     {
-        self.sql_builder.or_where_not_between(f, '?', '?');
-        self.arguments.add(min);
-        self.arguments.add(max);
+        self.sql_builder.or_where_not_between(self.ident(f), PARAM, PARAM);
+        self.bind(min);
+        self.bind(max);
         self
     }
 
@@ -317,9 +643,9 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + Sync + 'a
     {
-        self.sql_builder.or_where_in(f, &vec!["?"; v.len()]);
+        self.sql_builder.or_where_in(self.ident(f), &vec![PARAM; v.len()]);
         v.iter().for_each(|v| {
-            self.arguments.add(v);
+            self.bind(v);
         });
         self
     }
@@ -329,11 +655,53 @@ impl<'a> QueryBuilder<'a>{
             S: ToString,
             V: Encode<'a, Database> + Type<Database> + Send + Sync + 'a
     {
-        self.sql_builder.or_where_not_in(f, &vec!["?"; v.len()]);
+        self.sql_builder.or_where_not_in(self.ident(f), &vec![PARAM; v.len()]);
         v.iter().for_each(|v| {
-            self.arguments.add(v);
+            self.bind(v);
         });
         self
     }
 
 }
+
+/// Splits a JSON path like `$.tags[0].name` into the elements of a postgres path: `{tags,0,name}`.
+#[cfg(feature = "postgres")]
+fn json_path_elements(path: &str) -> Vec<String> {
+    path.trim_start_matches('$')
+        .split(&['.', '[', ']'][..])
+        .filter(|element| !element.is_empty())
+        .map(|element| element.trim_matches('"').to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::Arguments as SqlxArguments;
+
+    use crate::query::delete::Delete;
+    use crate::query::select::Select;
+    use crate::testing::{self, Pair, assert_arguments};
+
+    #[test]
+    fn composite_ids_match_every_column() {
+        let mut select = Select::<Pair>::new(testing::datasource());
+        select.query.and_where_ids::<Pair>(vec![(1, 2), (3, 4)]);
+        assert_eq!(
+            select.query_sql().unwrap(),
+            testing::sql("SELECT * FROM pair WHERE (a = ? AND b = ?) OR (a = ? AND b = ?);")
+        );
+        assert_arguments(select.take_arguments(), |arguments| {
+            arguments.add(1i64);
+            arguments.add(2i64);
+            arguments.add(3i64);
+            arguments.add(4i64);
+        });
+    }
+
+    #[test]
+    fn no_ids_match_no_rows() {
+        let mut delete = Delete::by_ids::<Pair>(testing::datasource(), &[]);
+        assert_eq!(delete.query.sql().unwrap(), "DELETE FROM pair WHERE 1 = 0;");
+        assert_arguments(delete.query.take_arguments(), |_| {});
+    }
+}
@@ -0,0 +1,231 @@
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::marker::PhantomData;
+use std::str::CharIndices;
+
+use anyhow::anyhow;
+use sqlx::{Arguments as SqlxArguments, Encode, Type};
+
+use crate::{Schema, connection};
+use crate::types::{Arguments, Database, QueryResult, Result, Transaction};
+
+type Param<'a> = Box<dyn Fn(&mut Arguments<'a>) + Send + Sync + 'a>;
+
+/// A query written in sql, with named parameters like `:id`.
+pub struct Raw<'a, T> {
+    _keep: PhantomData<T>,
+    datasource: TypeId,
+    sql: String,
+    params: HashMap<String, Param<'a>>,
+}
+
+impl<'a, T> Raw<'a, T> {
+
+    pub(crate) fn new(datasource: TypeId, sql: &str) -> Self {
+        Self { _keep: PhantomData, datasource, sql: sql.to_string(), params: HashMap::new() }
+    }
+
+    /// Bind `v` to the parameter `:name`, at every place it is used.
+    pub fn bind<V>(mut self, name: &str, v: V) -> Self
+        where V: Encode<'a, Database> + Type<Database> + Clone + Send + Sync + 'a
+    {
+        let name = name.trim_start_matches(':').to_string();
+        self.params.insert(name, Box::new(move |arguments| arguments.add(v.clone())));
+        self
+    }
+
+    /// The sql with the placeholders of the database, and the arguments in their order.
+    fn build(&self) -> Result<(String, Arguments<'a>)> {
+        let mut arguments = Arguments::default();
+        let mut count = 0;
+        let mut used = vec![];
+        let sql = replace_params(&self.sql, |name| {
+            let param = self.params.get(name).ok_or_else(|| anyhow!("Missing parameter `:{}`.", name))?;
+            param(&mut arguments);
+            used.push(name.to_string());
+            count += 1;
+            Ok(placeholder(count))
+        })?;
+        if let Some(name) = self.params.keys().find(|name| !used.contains(name)) {
+            return Err(anyhow!("Parameter `:{}` is not used.", name));
+        }
+        Ok((sql, arguments))
+    }
+
+    pub async fn execute(self) -> Result<QueryResult> {
+        let (sql, arguments) = self.build()?;
+        let result = sqlx::query_with(sql.as_str(), arguments)
+            .execute(connection::get(self.datasource)?).await?;
+        Ok(QueryResult::from(result))
+    }
+
+    pub async fn execute_with(self, tx: &mut Transaction<'_>) -> Result<QueryResult> {
+        let (sql, arguments) = self.build()?;
+        let result = sqlx::query_with(sql.as_str(), arguments).execute(tx).await?;
+        Ok(QueryResult::from(result))
+    }
+
+}
+
+impl<'a, T> Raw<'a, T> where T: Schema {
+
+    pub async fn fetch(self) -> Result<Option<T>> {
+        let (sql, arguments) = self.build()?;
+        let row = sqlx::query_with(sql.as_str(), arguments)
+            .fetch_optional(connection::get(self.datasource)?).await?;
+        row.map(|row| T::from_row(&row)).transpose()
+    }
+
+    pub async fn fetch_all(self) -> Result<Vec<T>> {
+        let (sql, arguments) = self.build()?;
+        let rows = sqlx::query_with(sql.as_str(), arguments)
+            .fetch_all(connection::get(self.datasource)?).await?;
+        rows.iter().map(T::from_row).collect()
+    }
+
+    pub async fn fetch_with(self, tx: &mut Transaction<'_>) -> Result<Option<T>> {
+        let (sql, arguments) = self.build()?;
+        let row = sqlx::query_with(sql.as_str(), arguments).fetch_optional(tx).await?;
+        row.map(|row| T::from_row(&row)).transpose()
+    }
+
+    pub async fn fetch_all_with(self, tx: &mut Transaction<'_>) -> Result<Vec<T>> {
+        let (sql, arguments) = self.build()?;
+        let rows = sqlx::query_with(sql.as_str(), arguments).fetch_all(tx).await?;
+        rows.iter().map(T::from_row).collect()
+    }
+
+}
+
+/// Stands in for the placeholders of the query builders, until `placeholders` replaces it.
+/// Unlike `?`, it can't be part of the sql written by hand, like the jsonb `?` operator of postgres.
+pub(crate) const PARAM: &str = "\u{0}?\u{0}";
+
+/// `PARAM` in place of the `?` of a holder of `Schema::holders`, other holders are sql expressions.
+pub(crate) fn param(holder: &'static str) -> &'static str {
+    match holder {
+        "?" => PARAM,
+        holder => holder,
+    }
+}
+
+/// Replaces each `PARAM` of a query with the placeholder of the database, numbered `$1`, `$2`, ...
+/// on postgres. Placeholders are written as `PARAM` until then, so that subqueries can be embedded anywhere.
+pub(crate) fn placeholders(sql: &str) -> String {
+    let mut parts = sql.split(PARAM);
+    let mut out = String::with_capacity(sql.len());
+    out.push_str(parts.next().unwrap_or_default());
+    for (n, part) in parts.enumerate() {
+        out.push_str(&placeholder(n + 1));
+        out.push_str(part);
+    }
+    out
+}
+
+#[cfg(feature = "postgres")]
+fn placeholder(n: usize) -> String {
+    format!("${}", n)
+}
+
+#[cfg(not(feature = "postgres"))]
+fn placeholder(_n: usize) -> String {
+    "?".to_string()
+}
+
+/// Replaces each `:name` of `sql` with `f(name)`, skipping quoted strings and names, comments
+/// and postgres casts like `::text`.
+fn replace_params<F>(sql: &str, mut f: F) -> Result<String>
+    where F: FnMut(&str) -> Result<String>
+{
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            _ if skip_quoted(sql, i, c, &mut chars, &mut out) => {}
+            ':' if chars.next_if(|(_, next)| *next == ':').is_some() => out.push_str("::"),
+            ':' if chars.peek().is_some_and(|(_, next)| next.is_ascii_alphabetic() || *next == '_') => {
+                let start = i + 1;
+                let mut end = start;
+                while let Some((j, next)) = chars.next_if(|(_, next)| next.is_ascii_alphanumeric() || *next == '_') {
+                    end = j + next.len_utf8();
+                }
+                out.push_str(&f(&sql[start..end])?);
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+/// Copies the quoted string or name, or the comment, starting with `c` at `i` to `out`.
+/// Returns false if `c` starts none of them.
+fn skip_quoted(sql: &str, i: usize, c: char, chars: &mut Peekable<CharIndices<'_>>, out: &mut String) -> bool {
+    match c {
+        '\'' | '"' | '`' => {
+            out.push(c);
+            for (_, next) in chars.by_ref() {
+                out.push(next);
+                if next == c {
+                    break;
+                }
+            }
+        }
+        '-' if sql[i..].starts_with("--") => {
+            let end = sql[i..].find('\n').map_or(sql.len(), |end| i + end);
+            out.push_str(&sql[i..end]);
+            while chars.next_if(|(j, _)| *j < end).is_some() {}
+        }
+        '/' if sql[i..].starts_with("/*") => {
+            let end = sql[i..].find("*/").map_or(sql.len(), |end| i + end + 2);
+            out.push_str(&sql[i..end]);
+            while chars.next_if(|(j, _)| *j < end).is_some() {}
+        }
+        _ => return false,
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replace(sql: &str) -> String {
+        replace_params(sql, |name| Ok(format!("<{}>", name))).unwrap()
+    }
+
+    #[test]
+    fn params_are_replaced() {
+        assert_eq!(replace("SELECT * FROM user WHERE id = :id"), "SELECT * FROM user WHERE id = <id>");
+        assert_eq!(replace("(:a,:b_2)"), "(<a>,<b_2>)");
+        assert_eq!(replace("WHERE a = :a OR b = :a"), "WHERE a = <a> OR b = <a>");
+    }
+
+    #[test]
+    fn quotes_and_comments_are_skipped() {
+        assert_eq!(replace("SELECT ':a', \"b:c\", `d:e`"), "SELECT ':a', \"b:c\", `d:e`");
+        assert_eq!(replace("SELECT 1 -- :a\nWHERE b = :b"), "SELECT 1 -- :a\nWHERE b = <b>");
+        assert_eq!(replace("SELECT /* :a */ :b"), "SELECT /* :a */ <b>");
+    }
+
+    #[test]
+    fn casts_and_colons_are_kept() {
+        assert_eq!(replace("SELECT :a::text"), "SELECT <a>::text");
+        assert_eq!(replace("SELECT '12:30', :1, : a"), "SELECT '12:30', :1, : a");
+    }
+
+    #[test]
+    fn only_params_are_placeholders() {
+        let sql = format!("SELECT * FROM a WHERE x = {0} AND data ? 'key' AND y IN ({0}, {0})", PARAM);
+        #[cfg(feature = "postgres")]
+        assert_eq!(placeholders(&sql), "SELECT * FROM a WHERE x = $1 AND data ? 'key' AND y IN ($2, $3)");
+        #[cfg(not(feature = "postgres"))]
+        assert_eq!(placeholders(&sql), "SELECT * FROM a WHERE x = ? AND data ? 'key' AND y IN (?, ?)");
+    }
+
+    #[test]
+    fn errors_are_returned() {
+        let result = replace_params("WHERE id = :id", |name| Err(anyhow!("Missing parameter `:{}`.", name)));
+        assert_eq!(result.unwrap_err().to_string(), "Missing parameter `:id`.");
+    }
+}
@@ -1,60 +1,128 @@
 use std::any::TypeId;
+use std::hash::Hash;
 use std::marker::PhantomData;
 
+use anyhow::anyhow;
 use sql_builder::SqlBuilder;
 use sqlx::encode::Encode;
 use sqlx::types::Type;
+use sqlx::{Decode, Row as SqlxRow};
 
-use crate::{Schema, connection, gen_where};
-use crate::query::query_builder::QueryBuilder;
+use crate::{Schema, Table, connection, expr, gen_table, gen_where, keyset};
+use crate::expr::{Expr, Extras};
+use crate::keyset::Key;
+use crate::query::preload::Preload;
+use crate::query::raw;
+use crate::query::query_builder::{Binding, Deleted, QueryBuilder};
 use crate::types::{Database, Result};
+use crate::types::Arguments;
+use crate::page::Page;
+use crate::types::{Row, Transaction};
+
+/// Column of the rows of a page, counting the rows of all pages.
+const TOTAL: &str = "cherry_total";
 
 pub struct Select<'a, T> {
     _keep: PhantomData<T>,
     pub(crate) query: QueryBuilder<'a>,
+    /// Applied by `sql()`, so that counts can leave them out.
+    pub(crate) order: Vec<(String, bool)>,
+    pub(crate) limit: Option<u64>,
+    pub(crate) offset: Option<u64>,
+    /// Whether the fields were chosen, rather than all columns.
+    fields: bool,
+    /// Alias and decoder of the expressions selected with `select_expr`.
+    extras: Vec<(String, expr::Decoder)>,
+    /// Selects combined by `union`, `intersect` or `except`, with their operator.
+    combined: Vec<String>,
+    combined_bindings: Vec<Binding<'a>>,
 }
 
 impl<'a, T> Select<'a, T> where T: Schema {
 
     pub(crate) fn new(datasource: TypeId) -> Self {
+        let mut query = QueryBuilder::new::<T>(datasource, |table| SqlBuilder::select_from(table));
+        query.deleted = Deleted::Excluded;
         Self {
             _keep: PhantomData,
-            query: QueryBuilder::new::<T>(datasource, SqlBuilder::select_from(T::table()))
+            query,
+            order: vec![],
+            limit: None,
+            offset: None,
+            fields: false,
+            extras: vec![],
+            combined: vec![],
+            combined_bindings: vec![],
         }
     }
 
+    /// Include soft deleted rows.
+    pub fn with_deleted(mut self) -> Self {
+        self.query.deleted = Deleted::Included;
+        self
+    }
+
+    /// Select soft deleted rows only.
+    pub fn only_deleted(mut self) -> Self {
+        self.query.deleted = Deleted::Only;
+        self
+    }
+
     pub fn field<S: ToString>(mut self, f: S) -> Self {
+        let f = self.query.ident(f);
         self.query.sql_builder.field(f);
+        self.fields = true;
         self
     }
 
     pub fn fields<S: ToString>(mut self, fields: &[S]) -> Self {
-        self.query.sql_builder.fields(fields);
+        let fields: Vec<String> = fields.iter().map(|f| self.query.ident(f.to_string())).collect();
+        self.query.sql_builder.fields(&fields);
+        self.fields = true;
         self
     }
 
     pub fn fields_all(mut self) -> Self {
-        self.query.sql_builder.fields(&T::columns());
+        let columns: Vec<String> = T::columns().iter().map(|f| self.query.ident(f)).collect();
+        self.query.sql_builder.fields(&columns);
+        self.fields = true;
+        self
+    }
+
+    /// Select `expr` as the extra column `alias`, decoded as `V` by
+    /// [fetch_extras](Self::fetch_extras) and [fetch_all_extras](Self::fetch_all_extras),
+    /// or into the `#[cherry(computed)]` field of the same name.
+    /// All columns are still selected, unless fields were chosen.
+    pub fn select_expr<V>(mut self, alias: &str, expr: Expr) -> Self
+        where V: for<'r> Decode<'r, Database> + Type<Database> + Send + 'static
+    {
+        if !self.fields {
+            self.query.sql_builder.field("*");
+            self.fields = true;
+        }
+        let field = format!("{} AS {}", expr, self.query.ident(alias));
+        self.query.sql_builder.field(field);
+        self.extras.push((alias.to_string(), expr::decode::<V>));
         self
     }
 
     pub fn order_asc<S: ToString>(mut self, f: S) -> Self {
-        self.query.sql_builder.order_asc(f);
+        self.order.push((self.query.ident(f), false));
         self
     }
 
     pub fn order_desc<S: ToString>(mut self, f: S) -> Self {
-        self.query.sql_builder.order_desc(f);
+        self.order.push((self.query.ident(f), true));
         self
     }
 
     pub fn limit(mut self, limit: usize) -> Self {
-        self.query.sql_builder.limit(limit);
+        self.limit = Some(limit as u64);
         self
     }
 
     pub fn offset(mut self, offset: usize) -> Self {
-        self.query.sql_builder.offset(offset);
+        self.offset = Some(offset as u64);
         self
     }
 
@@ -62,19 +130,206 @@ impl<'a, T> Select<'a, T> where T: Schema {
         where
             V: Encode<'a, Database> + Type<Database> + Send + 'a + ToString
     {
-        self.query.sql_builder.order_by(v, desc);
+        self.order.push((v.to_string(), desc));
+        self
+    }
+
+    /// Build the final sql, with the order, limit and offset, which apply to the combined rows
+    /// of a `union`.
+    pub(crate) fn sql(&self) -> Result<String> {
+        let mut sql = self.combined_sql()?.trim_end_matches(';').to_string();
+        if !self.order.is_empty() {
+            let order: Vec<String> = self.order.iter()
+                .map(|(f, desc)| format!("{}{}", f, if *desc { " DESC" } else { "" }))
+                .collect();
+            sql.push_str(&format!(" ORDER BY {}", order.join(", ")));
+        }
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+        if let Some(offset) = self.offset {
+            sql.push_str(&format!(" OFFSET {}", offset));
+        }
+        sql.push(';');
+        Ok(sql)
+    }
+
+    /// The sql to execute, with the placeholders of the database.
+    pub(crate) fn query_sql(&self) -> Result<String> {
+        Ok(raw::placeholders(&self.sql()?))
+    }
+
+    /// The sql of this select followed by the combined selects, without the order, limit and offset.
+    fn combined_sql(&self) -> Result<String> {
+        let sql = self.query.sql()?;
+        if self.combined.is_empty() {
+            return Ok(sql);
+        }
+        Ok(format!("{} {};", sql.trim_end_matches(';'), self.combined.join(" ")))
+    }
+
+    /// The bindings of this select, followed by those of the combined selects.
+    pub(crate) fn take_bindings(&mut self) -> Vec<Binding<'a>> {
+        let mut bindings = self.query.take_bindings();
+        bindings.append(&mut self.combined_bindings);
+        bindings
+    }
+
+    /// The arguments of the query, to be taken after building its sql.
+    pub(crate) fn take_arguments(&mut self) -> Arguments<'a> {
+        let mut arguments = std::mem::take(&mut self.query.arguments);
+        self.take_bindings().into_iter().for_each(|bind| bind(&mut arguments));
+        arguments
+    }
+
+    fn combine(mut self, operator: &str, other: Select<'a, T>) -> Self {
+        if !other.order.is_empty() || other.limit.is_some() || other.offset.is_some() {
+            self.query.error.get_or_insert(anyhow!(
+                "The order, limit and offset of combined selects apply to all rows, set them on the first select."
+            ));
+            return self;
+        }
+        if let Some((sql, bindings)) = self.query.subquery(other) {
+            self.combined.push(format!("{} {}", operator, sql));
+            self.combined_bindings.extend(bindings);
+        }
+        self
+    }
+
+    /// The rows of this select and of `other`, without duplicates.
+    pub fn union(self, other: Select<'a, T>) -> Self {
+        self.combine("UNION", other)
+    }
+
+    /// The rows of this select and of `other`, with duplicates.
+    pub fn union_all(self, other: Select<'a, T>) -> Self {
+        self.combine("UNION ALL", other)
+    }
+
+    /// The rows of this select which `other` selects too.
+    pub fn intersect(self, other: Select<'a, T>) -> Self {
+        self.combine("INTERSECT", other)
+    }
+
+    /// The rows of this select which `other` does not select.
+    pub fn except(self, other: Select<'a, T>) -> Self {
+        self.combine("EXCEPT", other)
+    }
+
+    /// Select from the rows of `select`, named `alias`, instead of the table.
+    /// The rows have to have the columns of `T`.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_select<U: Schema, S: ToString>(mut self, select: Select<'a, U>, alias: S) -> Self {
+        self.query.from_select(select, alias);
+        self
+    }
+
+    /// Prepend the cte `name`, the rows of `select`, to select from or join against.
+    pub fn with_cte<U: Schema, S: ToString>(mut self, name: S, select: Select<'a, U>) -> Self {
+        self.query.with_cte(name, select);
+        self
+    }
+
+    /// Prepend the recursive cte `name`: the rows of `anchor`, united with the rows of
+    /// `recursive`, which selects from `name`, until it returns no new rows.
+    pub fn with_recursive<U, V, S>(mut self, name: S, anchor: Select<'a, U>, recursive: Select<'a, V>) -> Self
+        where U: Schema, V: Schema, S: ToString
+    {
+        self.query.with_recursive(name, anchor, recursive);
+        self
+    }
+
+    /// `JOIN table ON left = right`, e.g. against a cte.
+    pub fn join<S, L, R>(mut self, table: S, left: L, right: R) -> Self
+        where S: ToString, L: ToString, R: ToString
+    {
+        self.query.sql_builder.inner();
+        self.query.join(table, left, right);
+        self
+    }
+
+    /// `LEFT JOIN table ON left = right`.
+    pub fn left_join<S, L, R>(mut self, table: S, left: L, right: R) -> Self
+        where S: ToString, L: ToString, R: ToString
+    {
+        self.query.sql_builder.left();
+        self.query.join(table, left, right);
         self
     }
 
+    gen_table!();
     gen_where!();
 
-    pub async fn fetch(self) -> Result<Option<T>> {
+    /// Preload the rows of `U` whose `foreign_key` column refers to the selected rows.
+    pub fn preload<U>(self, foreign_key: &str) -> Preload<'a, T, U>
+        where
+            T: Table,
+            U: Schema,
+            T::Id: Eq + Hash + Sync
+                + for<'q> Encode<'q, Database>
+                + for<'r> Decode<'r, Database>
+                + Type<Database>,
+    {
+        Preload::new(self, foreign_key)
+    }
+
+    /// Fetch the page after `cursor`, or the first page, ordered by the id columns.
+    pub async fn paginate_after(self, cursor: Option<&str>, page_size: usize) -> Result<keyset::Page<T>>
+        where T: Table, T::Id: Key
+    {
+        self.paginate_after_by(&T::id_columns(), T::id, cursor, page_size).await
+    }
+
+    /// Fetch the page after `cursor`, or the first page, ordered by `columns`.
+    /// `key` returns the values of `columns` of a row, which have to be unique together.
+    pub async fn paginate_after_by<K, F>(
+        mut self,
+        columns: &[&str],
+        key: F,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<keyset::Page<T>>
+        where K: Key, F: Fn(&T) -> K
+    {
+        if page_size == 0 {
+            return Err(anyhow!("Pages have at least one row."));
+        }
+        if columns.len() != K::LEN {
+            return Err(anyhow!("Expected {} key columns, got {}.", K::LEN, columns.len()));
+        }
+        let columns: Vec<String> = columns.iter().map(|f| self.query.ident(f)).collect();
+        if let Some(cursor) = cursor {
+            let after: K = keyset::decode(cursor)?;
+            self.query.sql_builder.and_where(format!(
+                "({}) > ({})",
+                columns.join(", "),
+                vec![raw::PARAM; columns.len()].join(", ")
+            ));
+            self.query.bindings.push(Box::new(move |arguments| after.add_arguments(arguments)));
+        }
+        // Ordered by the key first, since rows are only compared by the key.
+        self.order.splice(0..0, columns.into_iter().map(|f| (f, false)));
+        // One more row tells whether there is a next page.
+        self.limit = Some(page_size as u64 + 1);
+
+        let mut items = self.fetch_all().await?;
+        let next_cursor = match items.len() > page_size {
+            true => {
+                items.truncate(page_size);
+                items.last().map(|item| keyset::encode(&key(item)))
+            }
+            false => None,
+        };
+        Ok(keyset::Page { items, next_cursor })
+    }
+
+    pub async fn fetch(mut self) -> Result<Option<T>> {
 
-        let sql = self.query.sql_builder.sql()?;
+        let sql = self.query_sql()?;
 //         eprintln!("sql:{}",sql);
         let row = sqlx::query_with(
             sql.as_str(),
-            self.query.arguments
+            self.take_arguments()
         ).fetch_optional(connection::get(self.query.datasource)?).await?;
         match row {
             Some(row) => Ok(Some(T::from_row(&row)?)),
@@ -82,12 +337,11 @@ impl<'a, T> Select<'a, T> where T: Schema {
         }
     }
 
-    pub async fn fetch_all(self) -> Result<Vec<T>> {
-        let sql = self.query.sql_builder.sql()?;
-        eprintln!("sql:{}",sql);
+    pub async fn fetch_all(mut self) -> Result<Vec<T>> {
+        let sql = self.query_sql()?;
         let rows = sqlx::query_with(
             sql.as_str(),
-            self.query.arguments
+            self.take_arguments()
         ).fetch_all(connection::get(self.query.datasource)?).await?;
         let mut vec = Vec::with_capacity(rows.len());
         for row in rows {
@@ -96,4 +350,216 @@ impl<'a, T> Select<'a, T> where T: Schema {
         Ok(vec)
     }
 
+    /// Like [fetch](Self::fetch), with the expressions selected by `select_expr`.
+    pub async fn fetch_extras(mut self) -> Result<Option<(T, Extras)>> {
+        let sql = self.query_sql()?;
+        let row = sqlx::query_with(
+            sql.as_str(),
+            self.take_arguments()
+        ).fetch_optional(connection::get(self.query.datasource)?).await?;
+        match row {
+            Some(row) => Ok(Some((T::from_row(&row)?, Extras::from_row(&row, &self.extras)?))),
+            _ => Ok(None)
+        }
+    }
+
+    /// Like [fetch_all](Self::fetch_all), with the expressions selected by `select_expr`.
+    pub async fn fetch_all_extras(mut self) -> Result<Vec<(T, Extras)>> {
+        let sql = self.query_sql()?;
+        let rows = sqlx::query_with(
+            sql.as_str(),
+            self.take_arguments()
+        ).fetch_all(connection::get(self.query.datasource)?).await?;
+        rows.iter()
+            .map(|row| Ok((T::from_row(row)?, Extras::from_row(row, &self.extras)?)))
+            .collect()
+    }
+
+}
+
+/// Offset pagination, counting the rows of all pages in the same statement.
+impl<'a, T> Select<'a, T> where T: Schema {
+
+    /// The sql of `page`, selecting the number of rows of all pages as the column `TOTAL`.
+    fn page_sql(&mut self, page: u64, per_page: u64) -> Result<String> {
+        if page == 0 || per_page == 0 {
+            return Err(anyhow!("Pages are numbered from 1 and have at least one row."));
+        }
+        let offset = (page - 1).checked_mul(per_page)
+            .ok_or_else(|| anyhow!("Page {} of {} rows is out of range.", page, per_page))?;
+        let total = format!("COUNT(*) OVER () AS {}", TOTAL);
+        if self.combined.is_empty() {
+            if !self.fields {
+                self.query.sql_builder.field("*");
+                self.fields = true;
+            }
+            self.query.sql_builder.field(total);
+        } else {
+            // The combined rows are counted, and ordered, limited and offset as before.
+            let sql = self.combined_sql()?;
+            let mut sql_builder = SqlBuilder::select_from(format!("({}) AS page", sql.trim_end_matches(';')));
+            sql_builder.field("*").field(total);
+            // The scopes, ctes and combined selects are part of `sql` now, their bindings keep their order.
+            self.query.sql_builder = sql_builder;
+            self.query.bindings = self.take_bindings();
+            self.query.tenant = None;
+            self.query.ctes.clear();
+            self.query.deleted = Deleted::Included;
+            self.combined.clear();
+        }
+        self.limit = Some(per_page);
+        self.offset = Some(offset);
+        self.query_sql()
+    }
+
+    /// The rows of the page, and the total of their `TOTAL` column, 0 if there are none.
+    fn rows_to_page(rows: Vec<Row>, page: u64, per_page: u64) -> Result<Page<T>> {
+        let total: i64 = match rows.first() {
+            Some(row) => row.try_get(TOTAL)?,
+            None => 0,
+        };
+        let items = rows.iter().map(T::from_row).collect::<Result<Vec<_>>>()?;
+        Ok(Page::new(items, total as u64, page, per_page))
+    }
+
+    /// Fetch the rows of `page`, numbered from 1, and count the rows of all pages.
+    /// A page past the last one has no rows to count the others by, its total is 0.
+    pub async fn paginate(mut self, page: u64, per_page: u64) -> Result<Page<T>> {
+        let sql = self.page_sql(page, per_page)?;
+        let rows = sqlx::query_with(sql.as_str(), self.take_arguments())
+            .fetch_all(connection::get(self.query.datasource)?).await?;
+        Self::rows_to_page(rows, page, per_page)
+    }
+
+    /// Like [paginate](Self::paginate), in the transaction `tx`.
+    pub async fn paginate_with(mut self, page: u64, per_page: u64, tx: &mut Transaction<'_>) -> Result<Page<T>> {
+        let sql = self.page_sql(page, per_page)?;
+        let rows = sqlx::query_with(sql.as_str(), self.take_arguments()).fetch_all(&mut *tx).await?;
+        Self::rows_to_page(rows, page, per_page)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::Arguments as SqlxArguments;
+
+    use crate::DataSource;
+    use crate::query::ident::quote_always;
+    use crate::testing::{self, Invoice, Tests, assert_arguments};
+
+    #[test]
+    fn pages_count_their_rows_in_the_same_statement() {
+        let mut select = Tests.for_tenant(7i64).select::<Invoice>()
+            .and_where_gt("total", 5i64)
+            .order_desc("id");
+        assert_eq!(
+            select.page_sql(3, 20).unwrap(),
+            testing::sql("SELECT *, COUNT(*) OVER () AS cherry_total FROM invoice \
+                WHERE (total > ?) AND (invoice.tenant_id = ?) AND (invoice.deleted_at IS NULL) \
+                ORDER BY id DESC LIMIT 20 OFFSET 40;")
+        );
+        assert_arguments(select.take_arguments(), |arguments| {
+            arguments.add(5i64);
+            arguments.add(7i64);
+        });
+    }
+
+    #[test]
+    fn pages_of_combined_selects_count_the_combined_rows() {
+        let mut select = Tests.select::<Invoice>()
+            .and_where_gt("total", 5i64)
+            .union(Tests.select::<Invoice>().and_where_lt("total", 2i64))
+            .order_asc("id");
+        assert_eq!(
+            select.page_sql(1, 10).unwrap(),
+            testing::sql("SELECT *, COUNT(*) OVER () AS cherry_total FROM (\
+                SELECT * FROM invoice WHERE (total > ?) AND (invoice.deleted_at IS NULL) \
+                UNION SELECT * FROM invoice WHERE (total < ?) AND (invoice.deleted_at IS NULL)\
+                ) AS page ORDER BY id LIMIT 10 OFFSET 0;")
+        );
+        assert_arguments(select.take_arguments(), |arguments| {
+            arguments.add(5i64);
+            arguments.add(2i64);
+        });
+    }
+
+    #[test]
+    fn pages_out_of_range_are_rejected() {
+        assert!(Tests.select::<Invoice>().page_sql(0, 10).is_err());
+        assert!(Tests.select::<Invoice>().page_sql(u64::MAX, 2).is_err());
+    }
+
+    #[test]
+    fn subqueries_bind_in_the_order_of_the_statement() {
+        let cheap = Tests.select::<Invoice>().with_deleted().and_where_lt("total", 5i64);
+        let others = Tests.select::<Invoice>().with_deleted().field("tenant_id").and_where_gt("total", 100i64);
+        let mut select = Tests.select::<Invoice>()
+            .with_deleted()
+            .and_where_eq("id", 9i64)
+            .and_where_in_select("tenant_id", others)
+            .from_select(cheap, "cheap");
+        assert_eq!(
+            select.query_sql().unwrap(),
+            testing::sql(&format!(
+                "SELECT * FROM (SELECT * FROM invoice WHERE total < ?) AS {} \
+                WHERE (id = ?) AND (tenant_id IN (SELECT tenant_id FROM invoice WHERE total > ?));",
+                quote_always("cheap")
+            ))
+        );
+        assert_arguments(select.take_arguments(), |arguments| {
+            arguments.add(5i64);
+            arguments.add(9i64);
+            arguments.add(100i64);
+        });
+    }
+
+    #[test]
+    fn ctes_bind_before_the_subqueries_of_the_statement() {
+        let cheap = Tests.select::<Invoice>().with_deleted().and_where_lt("total", 5i64);
+        let big = Tests.select::<Invoice>().with_deleted().and_where_gt("total", 100i64);
+        // The cte is added last, its argument still comes first.
+        let mut select = Tests.select::<Invoice>()
+            .with_deleted()
+            .from_select(cheap, "cheap")
+            .and_where_eq("id", 9i64)
+            .join("big", "big.tenant_id", "cheap.tenant_id")
+            .with_cte("big", big);
+        assert_eq!(
+            select.query_sql().unwrap(),
+            testing::sql(&format!(
+                "WITH big AS (SELECT * FROM invoice WHERE total > ?) \
+                SELECT * FROM (SELECT * FROM invoice WHERE total < ?) AS {} \
+                INNER JOIN big ON big.tenant_id = cheap.tenant_id WHERE id = ?;",
+                quote_always("cheap")
+            ))
+        );
+        assert_arguments(select.take_arguments(), |arguments| {
+            arguments.add(100i64);
+            arguments.add(5i64);
+            arguments.add(9i64);
+        });
+    }
+
+    #[test]
+    fn unions_bind_each_select_before_the_next() {
+        let mut select = Tests.for_tenant(7i64).select::<Invoice>()
+            .and_where_gt("total", 5i64)
+            .union_all(Tests.for_tenant(7i64).select::<Invoice>().and_where_lt("total", 2i64))
+            .order_asc("id");
+        assert_eq!(
+            select.query_sql().unwrap(),
+            testing::sql("SELECT * FROM invoice \
+                WHERE (total > ?) AND (invoice.tenant_id = ?) AND (invoice.deleted_at IS NULL) \
+                UNION ALL SELECT * FROM invoice \
+                WHERE (total < ?) AND (invoice.tenant_id = ?) AND (invoice.deleted_at IS NULL) \
+                ORDER BY id;")
+        );
+        assert_arguments(select.take_arguments(), |arguments| {
+            arguments.add(5i64);
+            arguments.add(7i64);
+            arguments.add(2i64);
+            arguments.add(7i64);
+        });
+    }
 }
@@ -1,22 +1,86 @@
 use std::any::TypeId;
 
+use anyhow::anyhow;
 use sql_builder::SqlBuilder;
+use sqlx::Executor;
 use sqlx::encode::Encode;
 use sqlx::types::Type;
 
-use crate::{Schema, connection, gen_execute, gen_where};
-use crate::query::query_builder::QueryBuilder;
+use crate::{Schema, Table, connection, gen_execute, gen_table, gen_where};
+use crate::error::StaleObject;
+use crate::query::query_builder::{Deleted, QueryBuilder};
+use crate::query::raw;
 use crate::types::{Database, QueryResult, Result, Transaction};
 
 pub struct Update<'a> {
     pub(crate) query: QueryBuilder<'a>,
+    pub(crate) version: Option<&'static str>,
+    pub(crate) versioned: bool,
 }
 
 impl<'a> Update<'a> {
     pub(crate) fn new<T: Schema>(datasource: TypeId) -> Self {
-        Self {
-            query: QueryBuilder::new::<T>(datasource, SqlBuilder::update_table(T::table()))
+        let mut query = QueryBuilder::new::<T>(datasource, |table| SqlBuilder::update_table(table));
+        if let Some((column, holder)) = T::touch(&mut query.arguments) {
+            query.sql_builder.set(query.ident(column), raw::param(holder));
         }
+        Self { query, version: T::version_column(), versioned: false }
+    }
+
+    /// Clears the soft delete column of soft deleted rows.
+    pub(crate) fn restore<T: Schema>(datasource: TypeId) -> Self {
+        let mut query = QueryBuilder::new::<T>(datasource, |table| SqlBuilder::update_table(table));
+        if let Some(column) = T::soft_delete_column() {
+            query.sql_builder.set(query.ident(column), "NULL");
+        }
+        query.deleted = Deleted::Only;
+        Self { query, version: None, versioned: false }
+    }
+
+    /// Updates each of the `rows` by its id, setting every column to the value of its row with
+    /// `CASE id WHEN ? THEN ? .. END`. Rows of other ids, or soft deleted, are left as they are,
+    /// and so is the tenant column. Versioned tables are rejected, their versions can't be checked
+    /// row by row.
+    pub(crate) fn update_bulk<T: Table>(datasource: TypeId, rows: &'a [T]) -> Self {
+        let mut update = Self::new::<T>(datasource);
+        if let Some(column) = T::version_column() {
+            update.query.error.get_or_insert(anyhow!(
+                "Table `{}` has the version column `{}`, update its rows one by one to check their versions.",
+                T::table(), column
+            ));
+            return update;
+        }
+        let ids = T::id_columns();
+        let quoted: Vec<String> = ids.iter().map(|column| update.query.ident(column)).collect();
+        let (case, when) = match quoted.as_slice() {
+            [id] => (format!("CASE {}", id), format!("WHEN {0} THEN {0}", raw::PARAM)),
+            ids => {
+                let id: Vec<String> = ids.iter().map(|id| format!("{} = {}", id, raw::PARAM)).collect();
+                ("CASE".to_string(), format!("WHEN {} THEN {}", id.join(" AND "), raw::PARAM))
+            }
+        };
+        let whens = vec![when; rows.len()].join(" ");
+        let columns = T::columns().into_iter().zip(T::holders())
+            .filter(|(column, holder)| *holder == "?" && !ids.contains(column) && Some(*column) != T::tenant_column())
+            .map(|(column, _)| column)
+            .filter(|column| Some(*column) != T::updated_at_column());
+        let mut set = T::updated_at_column().is_some();
+        for column in columns.filter(|_| !rows.is_empty()) {
+            set = true;
+            let quoted = update.query.ident(column);
+            update.query.sql_builder.set(&quoted, format!("{} {} ELSE {} END", case, whens, quoted));
+            for row in rows {
+                T::id_arguments(row.id(), &mut update.query.arguments);
+                row.column_arguments(&[column], &mut update.query.arguments);
+            }
+        }
+        // Without rows nor columns to set, the statement still needs an assignment.
+        if !set {
+            update.query.sql_builder.set(&quoted[0], &quoted[0]);
+        }
+        update.query.deleted = Deleted::Excluded;
+        update.query.and_where_ids::<T>(rows.iter().map(Table::id).collect());
+        update
     }
 
     pub fn set<S, V>(mut self, f: S, v: V) -> Self
@@ -33,17 +97,147 @@ impl<'a> Update<'a> {
             S: ToString,
             V: Encode<'a, Database> + Type< Database> + Send + 'a
     {
-        self.query.sql_builder.set(f, '?');
+        self.query.sql_builder.set(self.query.ident(f), raw::PARAM);
         self.query.add_arguments(v);
         self
     }
 
+    gen_table!();
     gen_where!();
 
-    fn build_sql(&mut self) -> Result<String> {
-        Ok(self.query.sql_builder.sql()?)
+    pub(crate) fn build_sql(&mut self) -> Result<String> {
+        Ok(raw::placeholders(&self.query.sql()?))
     }
 
     gen_execute!();
 
+    /// Only update the row of the given version, incrementing it.
+    #[doc(hidden)]
+    pub fn with_version(mut self, version: i64) -> Self {
+        if let Some(column) = self.version {
+            let quoted = self.query.ident(column);
+            self.query.sql_builder.set(&quoted, format!("{} + 1", quoted));
+            self.query.and_where_eq(column, version);
+            self.versioned = true;
+        }
+        self
+    }
+
+    #[doc(hidden)]
+    pub async fn execute_on<'c, E>(mut self, db: E) -> sqlx::Result<QueryResult>
+        where E: Executor<'c, Database = Database>
+    {
+        let sql = self.build_sql()
+            .map_err(|err| sqlx::Error::Configuration(err.into()))?;
+        let result = sqlx::query_with(sql.as_str(), self.query.take_arguments())
+            .execute(db).await?;
+        if self.versioned && result.rows_affected() == 0 {
+            return Err(StaleObject::new(self.query.table).into());
+        }
+        Ok(QueryResult::from(result))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::Arguments as SqlxArguments;
+
+    use crate::{DataSource, Schema};
+    use crate::testing::{self, Invoice, Item, NOW, Pair, Tests, assert_arguments};
+
+    #[test]
+    fn patches_of_composite_ids_match_every_id_column() {
+        // As derived for `#[cherry(table = Pair, id(a, b))]`.
+        let name = "x".to_string();
+        let id = (1i64, 2i64);
+        let mut update = Pair::update()
+            .set("name", &name)
+            .and_where_eq("a", id.0)
+            .and_where_eq("b", id.1);
+        assert_eq!(update.build_sql().unwrap(), testing::sql("UPDATE pair SET name = ? WHERE (a = ?) AND (b = ?);"));
+        assert_arguments(update.query.take_arguments(), |arguments| {
+            arguments.add("x");
+            arguments.add(1i64);
+            arguments.add(2i64);
+        });
+    }
+
+    #[test]
+    fn bulk_updates_set_each_row_by_its_id() {
+        let rows = [Invoice { id: 1, tenant_id: 8, total: 10 }, Invoice { id: 2, tenant_id: 8, total: 20 }];
+        let mut update = Tests.update_bulk(&rows);
+        assert_eq!(
+            update.build_sql().unwrap(),
+            testing::sql("UPDATE invoice SET total = CASE id WHEN ? THEN ? WHEN ? THEN ? ELSE total END \
+                WHERE (id IN (?, ?)) AND (invoice.deleted_at IS NULL);")
+        );
+        assert_arguments(update.query.take_arguments(), |arguments| {
+            arguments.add(1i64);
+            arguments.add(10i64);
+            arguments.add(2i64);
+            arguments.add(20i64);
+            arguments.add(1i64);
+            arguments.add(2i64);
+        });
+    }
+
+    #[test]
+    fn bulk_updates_of_composite_ids_match_every_id_column() {
+        let rows = [Pair { a: 1, b: 2, name: "x".into() }, Pair { a: 3, b: 4, name: "y".into() }];
+        let mut update = Tests.update_bulk(&rows);
+        assert_eq!(
+            update.build_sql().unwrap(),
+            testing::sql("UPDATE pair SET name = CASE WHEN a = ? AND b = ? THEN ? WHEN a = ? AND b = ? THEN ? \
+                ELSE name END WHERE (a = ? AND b = ?) OR (a = ? AND b = ?);")
+        );
+        assert_arguments(update.query.take_arguments(), |arguments| {
+            arguments.add(1i64);
+            arguments.add(2i64);
+            arguments.add("x");
+            arguments.add(3i64);
+            arguments.add(4i64);
+            arguments.add("y");
+            arguments.add(1i64);
+            arguments.add(2i64);
+            arguments.add(3i64);
+            arguments.add(4i64);
+        });
+    }
+
+    #[test]
+    fn bulk_updates_touch_the_updated_at_column_once() {
+        let rows = [Item { id: 1, name: "x".into() }, Item { id: 2, name: "y".into() }];
+        let mut update = Tests.update_bulk(&rows);
+        assert_eq!(
+            update.build_sql().unwrap(),
+            testing::sql("UPDATE item SET updated_at = ?, name = CASE id WHEN ? THEN ? WHEN ? THEN ? ELSE name END \
+                WHERE id IN (?, ?);")
+        );
+        assert_arguments(update.query.take_arguments(), |arguments| {
+            arguments.add(NOW);
+            arguments.add(1i64);
+            arguments.add("x");
+            arguments.add(2i64);
+            arguments.add("y");
+            arguments.add(1i64);
+            arguments.add(2i64);
+        });
+    }
+
+    #[test]
+    fn bulk_updates_without_rows_match_nothing() {
+        let mut update = Tests.update_bulk::<Invoice>(&[]);
+        assert_eq!(
+            update.build_sql().unwrap(),
+            testing::sql("UPDATE invoice SET id = id WHERE (1 = 0) AND (invoice.deleted_at IS NULL);")
+        );
+        assert_arguments(update.query.take_arguments(), |_| {});
+
+        let mut update = Tests.update_bulk::<Item>(&[]);
+        assert_eq!(update.build_sql().unwrap(), testing::sql("UPDATE item SET updated_at = ? WHERE 1 = 0;"));
+        assert_arguments(update.query.take_arguments(), |arguments| {
+            arguments.add(NOW);
+        });
+    }
 }
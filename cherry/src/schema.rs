@@ -1,3 +1,6 @@
+use anyhow::anyhow;
+
+use crate::{CopyRow, Hooks};
 use crate::types::{Arguments, Result, Row};
 
 use std::any::TypeId;
@@ -16,12 +19,76 @@ pub trait Schema: Sized + Send + Unpin {
     /// Add each column's value as argument from appropriate field
     fn arguments<'a>(&'a self, arguments: &mut Arguments<'a>);
 
+    /// Add the values of the given columns as arguments, in the order of `columns()`
+    fn column_arguments<'a>(&'a self, columns: &[&str], arguments: &mut Arguments<'a>);
+
     /// Process row into data structure
     fn from_row(row: &Row) -> Result<Self>;
 
     /// Returns datasource to store and receive data for structure
     fn datasource() -> TypeId;
 
+    /// Return the schema qualifying the table, if any
+    fn schema() -> Option<&'static str> {
+        None
+    }
+
+    /// Whether table and column names are always quoted, not only reserved words
+    fn always_quote() -> bool {
+        false
+    }
+
+    /// Return the column marking rows as deleted, if the table is soft deleted
+    fn soft_delete_column() -> Option<&'static str> {
+        None
+    }
+
+    /// Return the column filled and filtered by a tenant scope, if the table is shared by tenants
+    fn tenant_column() -> Option<&'static str> {
+        None
+    }
+
+    /// Add the value of each column as argument like `arguments`, except the tenant column
+    fn tenant_arguments<'a>(&'a self, arguments: &mut Arguments<'a>) {
+        self.arguments(arguments)
+    }
+
+    /// Add each column's value to a row of a `COPY`, like `arguments` without the columns filled by the database.
+    /// Generated by the derive, rows of a model implemented by hand cannot be copied without it.
+    fn copy_arguments(&self, _row: &mut CopyRow) -> Result<()> {
+        Err(anyhow!("Table `{}` does not implement `Schema::copy_arguments`.", Self::table()))
+    }
+
+    /// Return the hooks of this row, if the model has `#[cherry(hooks)]`
+    fn hooks(&self) -> Option<&dyn Hooks> {
+        None
+    }
+
+    /// Return the hooks of this row mutably, to [normalize](Hooks::normalize) it
+    fn hooks_mut(&mut self) -> Option<&mut dyn Hooks> {
+        None
+    }
+
+    /// Return the column holding the row version, if the table uses optimistic locking
+    fn version_column() -> Option<&'static str> {
+        None
+    }
+
+    /// Return the value placeholder of each column, an sql expression for columns filled by the database
+    fn holders() -> Vec<&'static str> {
+        vec!["?"; Self::columns().len()]
+    }
+
+    /// Return the column holding the time of the last update, if any
+    fn updated_at_column() -> Option<&'static str> {
+        None
+    }
+
+    /// Add the current time as argument for the `updated_at` column, returning the column and its placeholder
+    fn touch<'a>(_arguments: &mut Arguments<'a>) -> Option<(&'static str, &'static str)> {
+        None
+    }
+
     // async fn begin<'a>(&'static self) -> Result<Transaction<'a>> {
     //     Ok(connection::get(Self::datasource())
     //         .map_err(|err|{sqlx::error::Error::Configuration(err.into())})?
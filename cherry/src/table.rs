@@ -20,7 +20,7 @@
 //! # Example: Patch
 //! ```rust,ignore
 //! #[derive(ormx::Patch)]
-//! #[ormx(table_name = "users", table = User, id = "id")]
+//! #[ormx(table = User, id = "id")]
 //! struct UpdateName {
 //!     first_name: String,
 //!     last_name: String,
@@ -37,7 +37,8 @@ use sqlx::{Executor, Result};
 
 pub use cherry_macros::*;
 use crate::{connection, Schema};
-use crate::types::{Pool};
+use crate::error::into_sqlx;
+use crate::types::{Arguments, Pool, Transaction};
 use async_trait::async_trait;
 
 
@@ -72,12 +73,29 @@ pub trait Table : Schema
     where
         Self: Sized + Send + Sync + 'static,
 {
-    /// Type of the ID column of this table.
+    /// Type of the ID column of this table, or a tuple for tables keyed by multiple columns.
     type Id: 'static + Copy + Send;
 
     /// Returns the id of this row.
     fn id(&self) -> Self::Id;
 
+    /// Number of id columns, the length of `id_columns()`.
+    const ID_COUNT: usize;
+
+    /// Returns the names of the id columns, in the order of `Self::Id`.
+    fn id_columns() -> Vec<&'static str>;
+
+    /// Adds the value of each id column as argument, in the order of `id_columns()`.
+    fn id_arguments(id: Self::Id, arguments: &mut Arguments<'_>);
+
+    /// Returns the version of this row, if the table uses optimistic locking.
+    fn version(&self) -> Option<i64> {
+        None
+    }
+
+    /// Increments the version of this row after it has been updated.
+    fn bump_version(&mut self) {}
+
     /// Returns connection to datasource
     fn pool() -> Result<&'static Pool>  {
         Ok(connection::get(Self::datasource())
@@ -147,23 +165,32 @@ pub trait Table : Schema
     {
         Box::pin(async move {
             let patch: P = patch;
-            patch.patch_row(self.id()).await?;
+            patch.patch_row_versioned_with(Self::pool()?, self.id(), self.version()).await?;
             patch.apply_to(self);
+            self.bump_version();
             Ok(())
         })
     }
 
     /// Updates all fields of this row, regardless if they have been changed or not.
     fn update(
-        &self,
+        &mut self,
     ) -> BoxFuture<Result<()>>;
 
     /// Updates all fields of this row, regardless if they have been changed or not.
+    /// With a version column, fails with [StaleObject](crate::error::StaleObject) if the row
+    /// was changed in the meantime, and bumps the version of this row otherwise.
     fn update_with<'a, 'c: 'a>(
-        &'a self,
+        &'a mut self,
         db: impl Executor<'c, Database = Db> + 'a,
     ) -> BoxFuture<'a, Result<()>>;
 
+    /// Like [update_with](Self::update_with), in `tx`, which is given to the hooks.
+    fn update_tx<'a, 'c: 'a>(
+        &'a mut self,
+        tx: &'a mut Transaction<'c>,
+    ) -> BoxFuture<'a, Result<()>>;
+
     // Refresh this row, querying all columns from the database.
     fn reload(
         &mut self,
@@ -195,17 +222,97 @@ pub trait Table : Schema
         self,
     ) -> BoxFuture<'a, Result<()>> {
         Box::pin(async move {
-            Self::delete_row_with(Self::pool()?, self.id()).await
+            self.delete_with(Self::pool()?).await
         })
     }
 
-    /// Deletes this row from the database.
+    /// Deletes this row from the database, running its hooks.
     fn delete_with<'a, 'c: 'a>(
         self,
         db: impl Executor<'c, Database = Db> + 'a,
     ) -> BoxFuture<'a, Result<()>> {
         Box::pin(async move {
-            Self::delete_row_with(db, self.id()).await
+            if let Some(hooks) = self.hooks() {
+                hooks.before_delete(None).await.map_err(into_sqlx)?;
+            }
+            Self::delete_row_with(db, self.id()).await?;
+            if let Some(hooks) = self.hooks() {
+                hooks.after_delete(None).await.map_err(into_sqlx)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Like [delete_with](Self::delete_with), in `tx`, which is given to the hooks.
+    fn delete_tx<'a, 'c: 'a>(
+        self,
+        tx: &'a mut Transaction<'c>,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            if let Some(hooks) = self.hooks() {
+                hooks.before_delete(Some(&mut *tx)).await.map_err(into_sqlx)?;
+            }
+            Self::delete_row_with(&mut *tx, self.id()).await?;
+            if let Some(hooks) = self.hooks() {
+                hooks.after_delete(Some(&mut *tx)).await.map_err(into_sqlx)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Delete a row from the database, even if the table is soft deleted
+    fn force_delete_row<'a>(
+        id: Self::Id,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            Self::force_delete_row_with(Self::pool()?, id).await
+        })
+    }
+
+    /// Delete a row from the database, even if the table is soft deleted
+    fn force_delete_row_with<'a, 'c: 'a>(
+        db: impl Executor<'c, Database = Db> + 'a,
+        id: Self::Id,
+    ) -> BoxFuture<'a, Result<()>> {
+        Self::delete_row_with(db, id)
+    }
+
+    /// Deletes this row from the database, even if the table is soft deleted.
+    fn force_delete<'a>(
+        self,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            Self::force_delete_row_with(Self::pool()?, self.id()).await
+        })
+    }
+
+    /// Restore a soft deleted row
+    fn restore_row<'a>(
+        id: Self::Id,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            Self::restore_row_with(Self::pool()?, id).await
+        })
+    }
+
+    /// Restore a soft deleted row
+    fn restore_row_with<'a, 'c: 'a>(
+        _db: impl Executor<'c, Database = Db> + 'a,
+        _id: Self::Id,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            Err(sqlx::Error::Configuration(
+                format!("Table `{}` has no soft delete column.", Self::table()).into()
+            ))
+        })
+    }
+
+    /// Restores this soft deleted row.
+    fn restore(
+        &self,
+    ) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            Self::restore_row_with(Self::pool()?, self.id()).await
         })
     }
 }
@@ -232,6 +339,23 @@ pub trait Patch
         &'a self,
         db: impl Executor<'c, Database = Db> + 'a,
         id: <Self::Table as Table>::Id,
+    ) -> BoxFuture<'a, Result<()>> {
+        self.patch_row_versioned_with(db, id, None)
+    }
+
+    /// Applies this patch to a row in the database, if it still has the given version.
+    fn patch_row_versioned_with<'a, 'c: 'a>(
+        &'a self,
+        db: impl Executor<'c, Database = Db> + 'a,
+        id: <Self::Table as Table>::Id,
+        version: Option<i64>,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    /// Applies this patch to a row in the database, in `tx`, which is given to the hooks.
+    fn patch_row_tx<'a, 'c: 'a>(
+        &'a self,
+        tx: &'a mut Transaction<'c>,
+        id: <Self::Table as Table>::Id,
     ) -> BoxFuture<'a, Result<()>>;
 }
 
@@ -0,0 +1,187 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use sqlx::{Arguments as SqlxArguments, Encode, Type};
+
+use crate::{DataSource, Schema, Table};
+use crate::query::delete::Delete;
+use crate::query::insert::Insert;
+use crate::query::insert_update::InsertUpdate;
+use crate::query::query_builder::Tenant;
+use crate::query::select::Select;
+use crate::query::update::Update;
+use crate::types::{Arguments, Database};
+
+/// The queries of a single tenant, created by [for_tenant](DataSource::for_tenant).
+///
+/// Every builder filters on the `#[cherry(tenant_column = ..)]` of its model, and inserted rows
+/// have it filled with the tenant. Models without a tenant column are rejected when the query runs.
+/// `insert_replace` is not scoped, since it could delete the row of another tenant.
+pub struct TenantScope<D: 'static> {
+    datasource: &'static D,
+    tenant: Tenant,
+}
+
+impl<D> TenantScope<D> where D: DataSource + 'static {
+
+    pub(crate) fn new<V>(datasource: &'static D, tenant: V) -> Self
+        where V: for<'q> Encode<'q, Database> + Type<Database> + Clone + Send + Sync + 'static
+    {
+        let tenant: Tenant = Arc::new(move |arguments: &mut Arguments| {
+            arguments.add(tenant.clone());
+        });
+        Self { datasource, tenant }
+    }
+
+    /// The datasource without the tenant filter.
+    pub fn unscoped(&self) -> &'static D {
+        self.datasource
+    }
+
+    pub fn insert<'a, T>(&self, v: &'a T) -> Insert<'a> where T: Schema + 'static {
+        Insert::insert(self.datasource.type_id(), v, Some(self.tenant.clone()))
+    }
+
+    pub fn insert_bulk<'a, T>(&self, v: &'a [T]) -> Insert<'a> where T: Schema + Sync + 'static {
+        Insert::insert_bulk(self.datasource.type_id(), v, Some(self.tenant.clone()))
+    }
+
+    pub fn insert_ignore<'a, T>(&self, v: &'a [T]) -> Insert<'a> where T: Schema + 'static {
+        Insert::insert_ignore(self.datasource.type_id(), v, Some(self.tenant.clone()))
+    }
+
+    /// Rows of other tenants with the same key are not updated.
+    pub fn insert_update<'a, T>(&self, v: &'a [T]) -> InsertUpdate<'a> where T: Schema + 'static {
+        InsertUpdate::insert_update(self.datasource.type_id(), v, Some(self.tenant.clone()))
+    }
+
+    pub fn delete<'a, T>(&self) -> Delete<'a> where T: Schema + 'static {
+        let mut delete = self.datasource.delete::<T>();
+        delete.query.tenant = Some(self.tenant.clone());
+        delete
+    }
+
+    pub fn delete_by_ids<'a, T>(&self, ids: &[T::Id]) -> Delete<'a> where T: Table {
+        let mut delete = self.datasource.delete_by_ids::<T>(ids);
+        delete.query.tenant = Some(self.tenant.clone());
+        delete
+    }
+
+    pub fn force_delete<'a, T>(&self) -> Delete<'a> where T: Schema + 'static {
+        let mut delete = self.datasource.force_delete::<T>();
+        delete.query.tenant = Some(self.tenant.clone());
+        delete
+    }
+
+    pub fn restore<'a, T>(&self) -> Update<'a> where T: Schema + 'static {
+        let mut update = self.datasource.restore::<T>();
+        update.query.tenant = Some(self.tenant.clone());
+        update
+    }
+
+    pub fn update<'a, T>(&self) -> Update<'a> where T: Schema + 'static {
+        let mut update = self.datasource.update::<T>();
+        update.query.tenant = Some(self.tenant.clone());
+        update
+    }
+
+    pub fn update_bulk<'a, T>(&self, v: &'a [T]) -> Update<'a> where T: Table {
+        let mut update = self.datasource.update_bulk(v);
+        update.query.tenant = Some(self.tenant.clone());
+        update
+    }
+
+    pub fn select<'a, T>(&self) -> Select<'a, T> where T: Schema + 'static {
+        let mut select = self.datasource.select::<T>();
+        select.query.tenant = Some(self.tenant.clone());
+        select
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::Arguments as SqlxArguments;
+
+    use crate::DataSource;
+    use crate::query::ident::quote_always;
+    use crate::testing::{self, Invoice, Tests, assert_arguments};
+
+    #[test]
+    fn tenant_is_and_ed_to_or_conditions() {
+        let mut select = Tests.for_tenant(7i64).select::<Invoice>()
+            .and_where_eq("total", 1i64)
+            .or_where_eq("total", 2i64);
+        assert_eq!(
+            select.query_sql().unwrap(),
+            testing::sql("SELECT * FROM invoice WHERE (total = ? OR total = ?) \
+                AND (invoice.tenant_id = ?) AND (invoice.deleted_at IS NULL);")
+        );
+        assert_arguments(select.take_arguments(), |arguments| {
+            arguments.add(1i64);
+            arguments.add(2i64);
+            arguments.add(7i64);
+        });
+    }
+
+    #[test]
+    fn tenant_follows_the_bindings_of_ctes_and_subqueries() {
+        let big = Tests.select::<Invoice>().and_where_gt("total", 100i64);
+        let cheap = Tests.select::<Invoice>().and_where_lt("total", 5i64);
+        let mut select = Tests.for_tenant(7i64).select::<Invoice>()
+            .with_cte("big", big)
+            .from_select(cheap, "cheap")
+            .and_where_eq("total", 3i64);
+        let cheap = quote_always("cheap");
+        assert_eq!(
+            select.query_sql().unwrap(),
+            testing::sql(&format!(
+                "WITH big AS (SELECT * FROM invoice WHERE (total > ?) AND (invoice.deleted_at IS NULL)) \
+                SELECT * FROM (SELECT * FROM invoice WHERE (total < ?) AND (invoice.deleted_at IS NULL)) AS {0} \
+                WHERE (total = ?) AND ({0}.tenant_id = ?) AND ({0}.deleted_at IS NULL);",
+                cheap
+            ))
+        );
+        assert_arguments(select.take_arguments(), |arguments| {
+            arguments.add(100i64);
+            arguments.add(5i64);
+            arguments.add(3i64);
+            arguments.add(7i64);
+        });
+    }
+
+    #[test]
+    fn bulk_updates_are_scoped() {
+        let rows = [Invoice { id: 1, tenant_id: 8, total: 10 }, Invoice { id: 2, tenant_id: 8, total: 20 }];
+        let mut update = Tests.for_tenant(7i64).update_bulk(&rows);
+        assert_eq!(
+            update.build_sql().unwrap(),
+            testing::sql("UPDATE invoice SET total = CASE id WHEN ? THEN ? WHEN ? THEN ? ELSE total END \
+                WHERE (id IN (?, ?)) AND (invoice.tenant_id = ?) AND (invoice.deleted_at IS NULL);")
+        );
+        assert_arguments(update.query.take_arguments(), |arguments| {
+            arguments.add(1i64);
+            arguments.add(10i64);
+            arguments.add(2i64);
+            arguments.add(20i64);
+            arguments.add(1i64);
+            arguments.add(2i64);
+            arguments.add(7i64);
+        });
+    }
+
+    #[test]
+    fn deletes_by_ids_are_scoped() {
+        let mut delete = Tests.for_tenant(7i64).delete_by_ids::<Invoice>(&[1, 2]);
+        assert_eq!(
+            delete.build_sql().unwrap(),
+            testing::sql("UPDATE invoice SET deleted_at = CURRENT_TIMESTAMP \
+                WHERE (id IN (?, ?)) AND (invoice.tenant_id = ?) AND (invoice.deleted_at IS NULL);")
+        );
+        assert_arguments(delete.query.take_arguments(), |arguments| {
+            arguments.add(1i64);
+            arguments.add(2i64);
+            arguments.add(7i64);
+        });
+    }
+}
@@ -0,0 +1,286 @@
+//! Models of the unit tests. The derives check their queries against a database while
+//! compiling, so these are implemented by hand.
+
+use std::any::TypeId;
+
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use sqlx::{Arguments as SqlxArguments, Executor};
+
+use crate::{DataSource, Schema, Table};
+use crate::query::raw;
+use crate::table::Db;
+use crate::types::{Arguments, Result, Row, Transaction};
+
+/// The datasource of the models, which is never connected.
+pub(crate) struct Tests;
+
+impl DataSource for Tests {}
+
+pub(crate) fn datasource() -> TypeId {
+    TypeId::of::<Tests>()
+}
+
+/// `sql`, written with `?`, with the placeholders of the database.
+pub(crate) fn sql(sql: &str) -> String {
+    raw::placeholders(&sql.replace('?', raw::PARAM))
+}
+
+/// Asserts that `arguments` holds the values added by `expected`, in their order.
+/// Arguments of postgres can't be read, so they are only compared on the other databases.
+pub(crate) fn assert_arguments<F>(arguments: Arguments<'_>, expected: F)
+    where F: for<'q> FnOnce(&mut Arguments<'q>)
+{
+    let mut expected_arguments = Arguments::default();
+    expected(&mut expected_arguments);
+    #[cfg(not(feature = "postgres"))]
+    assert_eq!(format!("{:?}", arguments), format!("{:?}", expected_arguments));
+    #[cfg(feature = "postgres")]
+    drop((arguments, expected_arguments));
+}
+
+/// The methods of `Table` which query the database, never called by the tests.
+macro_rules! unqueried {
+    () => {
+        fn get<'a>(_id: Self::Id) -> BoxFuture<'a, sqlx::Result<Self>> {
+            unimplemented!()
+        }
+
+        fn stream_all<'a>() -> BoxStream<'a, sqlx::Result<Self>> {
+            unimplemented!()
+        }
+
+        fn stream_all_paginated<'a>(_offset: i64, _limit: i64) -> BoxStream<'a, sqlx::Result<Self>> {
+            unimplemented!()
+        }
+
+        fn update(&mut self) -> BoxFuture<'_, sqlx::Result<()>> {
+            unimplemented!()
+        }
+
+        fn update_with<'a, 'c: 'a>(
+            &'a mut self,
+            _db: impl Executor<'c, Database = Db> + 'a,
+        ) -> BoxFuture<'a, sqlx::Result<()>> {
+            unimplemented!()
+        }
+
+        fn update_tx<'a, 'c: 'a>(&'a mut self, _tx: &'a mut Transaction<'c>) -> BoxFuture<'a, sqlx::Result<()>> {
+            unimplemented!()
+        }
+
+        fn delete_row_with<'a, 'c: 'a>(
+            _db: impl Executor<'c, Database = Db> + 'a,
+            _id: Self::Id,
+        ) -> BoxFuture<'a, sqlx::Result<()>> {
+            unimplemented!()
+        }
+    };
+}
+
+/// A table keyed by the columns `a` and `b`.
+pub(crate) struct Pair {
+    pub(crate) a: i64,
+    pub(crate) b: i64,
+    pub(crate) name: String,
+}
+
+impl Schema for Pair {
+    fn table() -> &'static str {
+        "pair"
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["a", "b", "name"]
+    }
+
+    fn arguments<'a>(&'a self, arguments: &mut Arguments<'a>) {
+        self.column_arguments(&Self::columns(), arguments)
+    }
+
+    fn column_arguments<'a>(&'a self, columns: &[&str], arguments: &mut Arguments<'a>) {
+        if columns.contains(&"a") {
+            arguments.add(self.a);
+        }
+        if columns.contains(&"b") {
+            arguments.add(self.b);
+        }
+        if columns.contains(&"name") {
+            arguments.add(&self.name);
+        }
+    }
+
+    fn from_row(_row: &Row) -> Result<Self> {
+        unimplemented!()
+    }
+
+    fn datasource() -> TypeId {
+        datasource()
+    }
+}
+
+impl Table for Pair {
+    type Id = (i64, i64);
+
+    const ID_COUNT: usize = 2;
+
+    fn id(&self) -> Self::Id {
+        (self.a, self.b)
+    }
+
+    fn id_columns() -> Vec<&'static str> {
+        vec!["a", "b"]
+    }
+
+    fn id_arguments(id: Self::Id, arguments: &mut Arguments<'_>) {
+        arguments.add(id.0);
+        arguments.add(id.1);
+    }
+
+    unqueried!();
+}
+
+/// A table shared by tenants, its rows soft deleted.
+pub(crate) struct Invoice {
+    pub(crate) id: i64,
+    pub(crate) tenant_id: i64,
+    pub(crate) total: i64,
+}
+
+impl Schema for Invoice {
+    fn table() -> &'static str {
+        "invoice"
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["id", "tenant_id", "total"]
+    }
+
+    fn arguments<'a>(&'a self, arguments: &mut Arguments<'a>) {
+        self.column_arguments(&Self::columns(), arguments)
+    }
+
+    fn column_arguments<'a>(&'a self, columns: &[&str], arguments: &mut Arguments<'a>) {
+        if columns.contains(&"id") {
+            arguments.add(self.id);
+        }
+        if columns.contains(&"tenant_id") {
+            arguments.add(self.tenant_id);
+        }
+        if columns.contains(&"total") {
+            arguments.add(self.total);
+        }
+    }
+
+    fn from_row(_row: &Row) -> Result<Self> {
+        unimplemented!()
+    }
+
+    fn datasource() -> TypeId {
+        datasource()
+    }
+
+    fn soft_delete_column() -> Option<&'static str> {
+        Some("deleted_at")
+    }
+
+    fn tenant_column() -> Option<&'static str> {
+        Some("tenant_id")
+    }
+
+    fn tenant_arguments<'a>(&'a self, arguments: &mut Arguments<'a>) {
+        self.column_arguments(&["id", "total"], arguments)
+    }
+}
+
+impl Table for Invoice {
+    type Id = i64;
+
+    const ID_COUNT: usize = 1;
+
+    fn id(&self) -> Self::Id {
+        self.id
+    }
+
+    fn id_columns() -> Vec<&'static str> {
+        vec!["id"]
+    }
+
+    fn id_arguments(id: Self::Id, arguments: &mut Arguments<'_>) {
+        arguments.add(id);
+    }
+
+    unqueried!();
+}
+
+/// A table touching its `updated_at` column on every update.
+pub(crate) struct Item {
+    pub(crate) id: i64,
+    pub(crate) name: String,
+}
+
+/// The time added by [Item::touch], fixed so that the arguments can be compared.
+pub(crate) const NOW: &str = "2026-01-01 00:00:00";
+
+impl Schema for Item {
+    fn table() -> &'static str {
+        "item"
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec!["id", "name", "updated_at"]
+    }
+
+    fn arguments<'a>(&'a self, arguments: &mut Arguments<'a>) {
+        self.column_arguments(&Self::columns(), arguments)
+    }
+
+    fn column_arguments<'a>(&'a self, columns: &[&str], arguments: &mut Arguments<'a>) {
+        if columns.contains(&"id") {
+            arguments.add(self.id);
+        }
+        if columns.contains(&"name") {
+            arguments.add(&self.name);
+        }
+        if columns.contains(&"updated_at") {
+            arguments.add(NOW);
+        }
+    }
+
+    fn from_row(_row: &Row) -> Result<Self> {
+        unimplemented!()
+    }
+
+    fn datasource() -> TypeId {
+        datasource()
+    }
+
+    fn updated_at_column() -> Option<&'static str> {
+        Some("updated_at")
+    }
+
+    fn touch<'a>(arguments: &mut Arguments<'a>) -> Option<(&'static str, &'static str)> {
+        arguments.add(NOW);
+        Some(("updated_at", "?"))
+    }
+}
+
+impl Table for Item {
+    type Id = i64;
+
+    const ID_COUNT: usize = 1;
+
+    fn id(&self) -> Self::Id {
+        self.id
+    }
+
+    fn id_columns() -> Vec<&'static str> {
+        vec!["id"]
+    }
+
+    fn id_arguments(id: Self::Id, arguments: &mut Arguments<'_>) {
+        arguments.add(id);
+    }
+
+    unqueried!();
+}
@@ -0,0 +1,57 @@
+/// A column type which can be filled with the current time,
+/// see `#[cherry(created_at)]` and `#[cherry(updated_at)]`.
+pub trait Timestamp {
+    /// Returns the current time.
+    fn now() -> Self;
+}
+
+impl<T: Timestamp> Timestamp for Option<T> {
+    fn now() -> Self {
+        Some(T::now())
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono {
+    use sqlx::types::chrono::{DateTime, Local, NaiveDateTime, Utc};
+
+    use super::Timestamp;
+
+    impl Timestamp for DateTime<Utc> {
+        fn now() -> Self {
+            Utc::now()
+        }
+    }
+
+    impl Timestamp for DateTime<Local> {
+        fn now() -> Self {
+            Local::now()
+        }
+    }
+
+    impl Timestamp for NaiveDateTime {
+        fn now() -> Self {
+            Utc::now().naive_utc()
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time {
+    use sqlx::types::time::{OffsetDateTime, PrimitiveDateTime};
+
+    use super::Timestamp;
+
+    impl Timestamp for OffsetDateTime {
+        fn now() -> Self {
+            OffsetDateTime::now_utc()
+        }
+    }
+
+    impl Timestamp for PrimitiveDateTime {
+        fn now() -> Self {
+            let now = OffsetDateTime::now_utc();
+            PrimitiveDateTime::new(now.date(), now.time())
+        }
+    }
+}
@@ -0,0 +1,165 @@
+#![cfg(any(feature = "mysql", feature = "postgres", feature = "sqlite"))]
+use std::ops::Deref;
+
+use sqlx::Executor;
+
+use crate::{Column, Db, Schema, Table};
+use crate::error::into_sqlx;
+use crate::query::raw;
+use crate::query::update::Update;
+use crate::types::Transaction;
+
+/// A row which records the columns assigned through [set](Tracked::set),
+/// so that [save](Tracked::save) only updates those.
+pub struct Tracked<T> {
+    row: T,
+    dirty: Vec<&'static str>,
+}
+
+impl<T> Tracked<T> where T: Table {
+
+    pub fn new(row: T) -> Self {
+        Self { row, dirty: vec![] }
+    }
+
+    /// Change the field of `column` with `f`, marking the column as changed.
+    /// The id columns can not be changed, saving fails if one of them is set.
+    /// The column constants are generated with `#[cherry(columns)]`, e.g. `User::NAME`.
+    pub fn set<F>(&mut self, column: Column<T>, f: F) -> &mut Self
+        where F: FnOnce(&mut T)
+    {
+        f(&mut self.row);
+        if !self.dirty.contains(&column.name()) {
+            self.dirty.push(column.name());
+        }
+        self
+    }
+
+    /// Returns the columns changed since the last save.
+    pub fn dirty_columns(&self) -> &[&'static str] {
+        &self.dirty
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.row
+    }
+
+    /// Updates the changed columns of this row.
+    pub async fn save(&mut self) -> sqlx::Result<()> {
+        self.save_with(T::pool()?).await
+    }
+
+    /// Updates the changed columns of this row, failing with `RowNotFound` if it no longer exists.
+    /// Versioned rows fail with [StaleObject](crate::error::StaleObject) if they changed in the meantime.
+    pub async fn save_with<'c>(&mut self, db: impl Executor<'c, Database = Db>) -> sqlx::Result<()> {
+        if !self.before_save()? {
+            return Ok(());
+        }
+        if let Some(hooks) = self.row.hooks() {
+            hooks.before_update(None).await.map_err(into_sqlx)?;
+        }
+        let result = self.update().execute_on(db).await?;
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        self.after_save();
+        if let Some(hooks) = self.row.hooks() {
+            hooks.after_update(None).await.map_err(into_sqlx)?;
+        }
+        Ok(())
+    }
+
+    /// Like [save_with](Self::save_with), in `tx`, which is given to the hooks.
+    pub async fn save_tx(&mut self, tx: &mut Transaction<'_>) -> sqlx::Result<()> {
+        if !self.before_save()? {
+            return Ok(());
+        }
+        if let Some(hooks) = self.row.hooks() {
+            hooks.before_update(Some(&mut *tx)).await.map_err(into_sqlx)?;
+        }
+        let result = self.update().execute_on(&mut *tx).await?;
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        self.after_save();
+        if let Some(hooks) = self.row.hooks() {
+            hooks.after_update(Some(&mut *tx)).await.map_err(into_sqlx)?;
+        }
+        Ok(())
+    }
+
+    /// Checks the changed columns and normalizes the row, returning whether there is anything to save.
+    /// Only the changed columns are saved, whatever `normalize` changes.
+    fn before_save(&mut self) -> sqlx::Result<bool> {
+        if self.dirty.is_empty() {
+            return Ok(false);
+        }
+        let columns = T::columns();
+        if let Some(column) = self.dirty.iter().find(|column| !columns.contains(column)) {
+            return Err(sqlx::Error::ColumnNotFound(column.to_string()));
+        }
+        let id_columns = T::id_columns();
+        if let Some(column) = self.dirty.iter().find(|column| id_columns.contains(column)) {
+            return Err(sqlx::Error::Configuration(
+                format!("The id column `{}` can not be changed by saving a tracked row.", column).into()
+            ));
+        }
+        if let Some(hooks) = self.row.hooks_mut() {
+            hooks.normalize();
+        }
+        Ok(true)
+    }
+
+    fn after_save(&mut self) {
+        self.row.bump_version();
+        self.dirty.clear();
+    }
+
+    /// The update of the changed columns of this row.
+    fn update(&self) -> Update<'_> {
+        let columns = T::columns();
+        let managed = [T::updated_at_column(), T::version_column()];
+        let changed: Vec<&'static str> = columns.iter()
+            .filter(|column| self.dirty.contains(column) && !managed.contains(&Some(**column)))
+            .copied()
+            .collect();
+        let id_columns = T::id_columns();
+        let id_columns: Vec<&'static str> = columns.iter()
+            .filter(|column| id_columns.contains(column))
+            .copied()
+            .collect();
+
+        let mut update = <T as Schema>::update();
+        changed.iter().for_each(|column| {
+            update.query.sql_builder.set(update.query.ident(column), raw::PARAM);
+        });
+        self.row.column_arguments(&changed, &mut update.query.arguments);
+        id_columns.iter().for_each(|column| {
+            update.query.sql_builder.and_where_eq(update.query.ident(column), raw::PARAM);
+        });
+        self.row.column_arguments(&id_columns, &mut update.query.arguments);
+        if let Some(version) = self.row.version() {
+            update = update.with_version(version);
+        }
+        update
+    }
+
+}
+
+impl<T> From<T> for Tracked<T> where T: Table {
+    fn from(row: T) -> Self {
+        Self::new(row)
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.row
+    }
+}
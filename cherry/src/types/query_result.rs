@@ -20,6 +20,18 @@ impl QueryResult {
     pub fn last_insert_rowid(&self) -> i64 {
         self.last_insert_rowid
     }
+
+    /// Adds up the result of the `next` statement of a query run in chunks. The insert id
+    /// stays the one of the first row, and the rowid becomes the one of the last row.
+    pub(crate) fn merge(self, next: QueryResult) -> Self {
+        Self {
+            rows_affected: self.rows_affected + next.rows_affected,
+            #[cfg(feature = "mysql")]
+            last_insert_id: self.last_insert_id,
+            #[cfg(feature = "sqlite")]
+            last_insert_rowid: next.last_insert_rowid,
+        }
+    }
 }
 
 macro_rules! gen_query_result {